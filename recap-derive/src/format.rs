@@ -0,0 +1,103 @@
+//! Translates `#[recap(format = "...")]` strings into the regex a type is matched by
+//!
+//! Each `{name}` placeholder becomes a named capture group matching `\S+`, or the
+//! pattern following a `:` as in `{name:\d+}`. Text outside of placeholders is
+//! matched literally, with `{{` and `}}` standing for literal braces.
+
+/// Translates a format string into a regex, or a description of why it is invalid
+pub(crate) fn to_regex(format: &str) -> Result<String, String> {
+    let mut regex = String::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '}' => return Err("an unmatched `}`. Literal braces are written `}}`".into()),
+            '{' => {
+                regex.push_str(&regex_syntax::escape(&literal));
+                literal.clear();
+                let placeholder = placeholder(&mut chars)?;
+                let (name, pattern) = placeholder
+                    .split_once(':')
+                    .unwrap_or((placeholder.as_str(), r"\S+"));
+                let mut name_chars = name.chars();
+                let valid = name_chars
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && name_chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(format!("an invalid placeholder name `{}`", name));
+                }
+                regex.push_str(&format!("(?P<{}>{})", name, pattern));
+            }
+            c => literal.push(c),
+        }
+    }
+    regex.push_str(&regex_syntax::escape(&literal));
+    Ok(regex)
+}
+
+/// Reads the contents of a placeholder up to its closing `}`. Braces within a
+/// custom pattern, such as those of `\d{2}`, are balanced or escaped
+fn placeholder(chars: &mut impl Iterator<Item = char>) -> Result<String, String> {
+    let mut placeholder = String::new();
+    let mut depth = 0usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '}' if depth == 0 => return Ok(placeholder),
+            '}' => depth -= 1,
+            '{' => depth += 1,
+            '\\' => {
+                placeholder.push(c);
+                match chars.next() {
+                    Some(escaped) => placeholder.push(escaped),
+                    None => break,
+                }
+                continue;
+            }
+            _ => (),
+        }
+        placeholder.push(c);
+    }
+    Err(format!("an unclosed placeholder `{{{}`", placeholder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_regex;
+
+    #[test]
+    fn translates_placeholders_and_literals() {
+        assert_eq!(
+            to_regex("{from} to {to} = {dist:\\d+}"),
+            Ok(r"(?P<from>\S+) to (?P<to>\S+) = (?P<dist>\d+)".to_string())
+        );
+        assert_eq!(
+            to_regex("v{major:\\d{1,2}}.{{{minor}}}"),
+            Ok(r"v(?P<major>\d{1,2})\.\{(?P<minor>\S+)\}".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_formats() {
+        assert_eq!(
+            to_regex("{a} }"),
+            Err("an unmatched `}`. Literal braces are written `}}`".to_string())
+        );
+        assert_eq!(
+            to_regex("{a:\\d+"),
+            Err("an unclosed placeholder `{a:\\d+`".to_string())
+        );
+        assert_eq!(
+            to_regex("{1a}"),
+            Err("an invalid placeholder name `1a`".to_string())
+        );
+    }
+}
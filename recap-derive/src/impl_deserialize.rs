@@ -0,0 +1,145 @@
+//! Generates a `serde::Deserialize` impl for types declaring `#[recap(handle_deserialize)]`
+//!
+//! The generated impl accepts either a string, which is parsed with the type's own
+//! recap regex, or a map, as produced by self-describing formats like JSON or by a
+//! field level recap regex. Maps are forwarded to `__DeserializeHelper`, a serde derived
+//! mirror of the type which retains its `#[serde(...)]` attributes.
+//!
+//! A container level `#[serde(default)]` defaults the mirror's missing fields from the
+//! type's own `Default` impl.
+//!
+//! serde is referenced through its re-export from recap, so types need not
+//! depend on serde directly to declare `handle_deserialize`.
+//!
+//! Generic types and types with lifetimes are not supported. Recursive types
+//! such as `next: Option<Box<Self>>` are, with `Self` resolving to the type itself.
+
+use proc_macro2::{Group, Ident, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::DeriveInput;
+
+use crate::{get_nested_metas, get_serde_metas, has_flag, named_fields};
+
+pub(crate) fn impl_deserialize(item: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            format!(
+                "Recap `handle_deserialize` does not support generic types or lifetimes on `{}`",
+                item.ident
+            ),
+        ));
+    }
+    let fields = named_fields(item)?;
+
+    let item_ident = &item.ident;
+    let container_attrs = item.attrs.iter().filter(|a| a.path.is_ident("serde"));
+    let helper_fields = fields.iter().map(|field| {
+        let attrs = field.attrs.iter().filter(|a| a.path.is_ident("serde"));
+        let ident = &field.ident;
+        let ty = replace_self(field.ty.to_token_stream(), item_ident);
+        let skip = if has_flag(&get_nested_metas(&field.attrs), "skip") {
+            quote!(#[serde(skip_deserializing)])
+        } else {
+            quote!()
+        };
+        quote!(#(#attrs)* #skip #ident: #ty)
+    });
+    let field_idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    // a container level `#[serde(default)]` requires the mirror to implement
+    // `Default`, which it does by taking the fields of the type's own default
+    let impl_default = if has_flag(&get_serde_metas(&item.attrs), "default") {
+        quote! {
+            impl Default for __DeserializeHelper {
+                fn default() -> Self {
+                    let value = <#item_ident as Default>::default();
+                    __DeserializeHelper {
+                        #(#field_idents: value.#field_idents,)*
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let expecting = format!(
+        "a string matching the recap regex for {} or a map",
+        item_ident
+    );
+
+    Ok(quote! {
+        #[derive(recap::_serde::Deserialize)]
+        #[serde(crate = "recap::_serde")]
+        #(#container_attrs)*
+        struct __DeserializeHelper {
+            #(#helper_fields,)*
+        }
+
+        #impl_default
+
+        impl From<__DeserializeHelper> for #item_ident {
+            fn from(helper: __DeserializeHelper) -> Self {
+                #item_ident {
+                    #(#field_idents: helper.#field_idents,)*
+                }
+            }
+        }
+
+        impl<'de> recap::_serde::Deserialize<'de> for #item_ident {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: recap::_serde::Deserializer<'de>,
+            {
+                struct __Visitor;
+
+                impl<'de> recap::_serde::de::Visitor<'de> for __Visitor {
+                    type Value = #item_ident;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                    where
+                        E: recap::_serde::de::Error,
+                    {
+                        <#item_ident as std::str::FromStr>::from_str(s).map_err(E::custom)
+                    }
+
+                    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: recap::_serde::de::MapAccess<'de>,
+                    {
+                        <__DeserializeHelper as recap::_serde::Deserialize>::deserialize(
+                            recap::_serde::de::value::MapAccessDeserializer::new(map),
+                        )
+                        .map(Into::into)
+                    }
+                }
+
+                deserializer.deserialize_any(__Visitor)
+            }
+        }
+    })
+}
+
+/// Replaces `Self` with the type's own ident so that the mirror's fields
+/// refer to the type rather than to the mirror
+fn replace_self(
+    tokens: TokenStream2,
+    item_ident: &Ident,
+) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(ident) if ident == "Self" => TokenTree::Ident(item_ident.clone()),
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    Group::new(group.delimiter(), replace_self(group.stream(), item_ident));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            other => other,
+        })
+        .collect()
+}
@@ -0,0 +1,93 @@
+//! Generates field by field parsing for types declaring `#[recap(from_str)]`
+//!
+//! Rather than deserializing captures with serde, each field is parsed from its
+//! named capture with the field type's own `FromStr` impl. `Option<T>` fields
+//! parse `T` and resolve to `None` when their capture did not participate.
+//!
+//! A newtype's value, like a single field without a named group, is parsed from the
+//! whole match of its regex, which validates the input before `FromStr` is applied.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Field, GenericArgument, Member, PathArguments, Type};
+
+/// Generates an expression parsing `s` into `Self` with the given regex static,
+/// aggregating the errors of all fields which failed to parse
+pub(crate) fn parse_fields(
+    item: &DeriveInput,
+    re: &TokenStream2,
+) -> syn::Result<TokenStream2> {
+    // a newtype's value is parsed from the whole match, bound to a group named after it
+    let fields: Vec<(Member, &Field, String)> = match crate::newtype_field(item) {
+        Some(field) => vec![(Member::Unnamed(0.into()), field, crate::newtype_group(item))],
+        None => crate::named_fields(item)?
+            .iter()
+            .map(|field| {
+                let ident = field.ident.clone().expect("named field");
+                let field_name = syn::ext::IdentExt::unraw(&ident).to_string();
+                (Member::Named(ident), field, field_name)
+            })
+            .collect(),
+    };
+
+    let mut parsed = Vec::new();
+    let mut required = Vec::new();
+    let mut assignments = Vec::new();
+    for (ident, field, field_name) in fields {
+        if crate::is_skipped(field) {
+            assignments.push(quote!(#ident: Default::default()));
+            continue;
+        }
+        let var = format_ident!("__field_{}", field_name);
+        let name = crate::capture_name_tokens(item, &field_name)?;
+        match option_inner(&field.ty) {
+            Some(inner) => {
+                parsed.push(quote! {
+                    let #var = recap::parse_capture::<#inner>(&caps, #name, false, &mut errors);
+                });
+                assignments.push(quote!(#ident: #var));
+            }
+            None => {
+                let ty = &field.ty;
+                parsed.push(quote! {
+                    let #var = recap::parse_capture::<#ty>(&caps, #name, true, &mut errors);
+                });
+                required.push(var.clone());
+                assignments.push(quote!(#ident: #var));
+            }
+        }
+    }
+
+    Ok(quote! {{
+        let caps = #re.captures(s).ok_or_else(|| {
+            recap::Error::Custom(format!("No captures resolved in string '{}'", s))
+        })?;
+        let mut errors = Vec::new();
+        #(#parsed)*
+        if let (#(Some(#required),)* true) = (#(#required,)* errors.is_empty()) {
+            Ok(Self {
+                #(#assignments,)*
+            })
+        } else {
+            Err(recap::Error::Custom(errors.join("; ")))
+        }
+    }})
+}
+
+/// Resolves `T` of an `Option<T>` type
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,131 @@
+//! Generates a `serde::Serialize` impl for types declaring `#[recap(serialize)]`
+//!
+//! Values are serialized as the string their type's regex would match, rebuilt from
+//! the pattern's literal text and the `Display` impls of each captured field. Only
+//! patterns made of literal text, anchors and capture groups can be rebuilt this way.
+//! Classes, repetitions and alternations outside of named groups can not, as there is
+//! no single string they would match.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use regex_syntax::hir::{Hir, HirKind};
+use syn::{ext::IdentExt, DeriveInput, Meta};
+
+/// A piece of a rebuilt pattern
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Field(String),
+}
+
+pub(crate) fn impl_serialize(
+    item: &DeriveInput,
+    metas: &[Meta],
+    pattern: &str,
+) -> syn::Result<TokenStream2> {
+    let fields = crate::named_fields(item)?;
+    let pieces = pieces(pattern).map_err(|reason| {
+        crate::option_error(
+            metas,
+            "serialize",
+            &item.ident,
+            format!(
+                "Recap `serialize` on `{}` requires a pattern of literal text and capture groups, but it contains {}",
+                item.ident, reason
+            ),
+        )
+    })?;
+
+    let writes = pieces.iter().map(|piece| match piece {
+        Piece::Literal(literal) => quote!(f.write_str(#literal)?;),
+        Piece::Field(name) => {
+            let ident = fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .find(|ident| crate::names_field(item, name, &ident.unraw().to_string()))
+                .expect("validated capture name");
+            quote!(std::fmt::Display::fmt(&self.0.#ident, f)?;)
+        }
+    });
+    let item_ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics recap::_serde::Serialize for #item_ident #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: recap::_serde::Serializer,
+            {
+                struct __Render<'r, T: ?Sized>(&'r T);
+
+                impl #impl_generics std::fmt::Display for __Render<'_, #item_ident #ty_generics> #where_clause {
+                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        #(#writes)*
+                        Ok(())
+                    }
+                }
+
+                serializer.collect_str(&__Render(self))
+            }
+        }
+    })
+}
+
+/// Resolves the pieces a pattern's matches are made of, or a description
+/// of the part of the pattern which prevents it from being rebuilt
+fn pieces(pattern: &str) -> Result<Vec<Piece>, String> {
+    let hir = regex_syntax::parse(pattern).map_err(|err| err.to_string())?;
+    let mut pieces = Vec::new();
+    collect_pieces(&hir, &mut pieces)?;
+    Ok(pieces)
+}
+
+fn collect_pieces(
+    hir: &Hir,
+    pieces: &mut Vec<Piece>,
+) -> Result<(), String> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Ok(()),
+        HirKind::Literal(literal) => {
+            let text = String::from_utf8_lossy(&literal.0);
+            match pieces.last_mut() {
+                Some(Piece::Literal(last)) => last.push_str(&text),
+                _ => pieces.push(Piece::Literal(text.into_owned())),
+            }
+            Ok(())
+        }
+        HirKind::Capture(capture) => match &capture.name {
+            Some(name) => {
+                pieces.push(Piece::Field(name.to_string()));
+                Ok(())
+            }
+            None => collect_pieces(&capture.sub, pieces),
+        },
+        HirKind::Concat(hirs) => hirs.iter().try_for_each(|hir| collect_pieces(hir, pieces)),
+        HirKind::Class(_) => Err("a character class".into()),
+        HirKind::Repetition(_) => Err("a repetition".into()),
+        HirKind::Alternation(_) => Err("an alternation".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pieces, Piece};
+
+    #[test]
+    fn rebuilds_literals_and_fields() {
+        assert_eq!(
+            pieces(r#"^(?P<host>[^:]+):(?P<port>\d+)/(?:v1)$"#),
+            Ok(vec![
+                Piece::Field("host".into()),
+                Piece::Literal(":".into()),
+                Piece::Field("port".into()),
+                Piece::Literal("/v1".into()),
+            ])
+        );
+        assert_eq!(
+            pieces(r#"(?P<a>\d+)\s(?P<b>\d+)"#),
+            Err("a character class".into())
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Expands `recap!("pattern", input => Type)` into a one-off parse of some input
+//!
+//! The pattern is validated when the macro is expanded and compiled once, on first
+//! use, into a static which is private to the expanded block.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use regex::Regex;
+use syn::{
+    parse::{Parse, ParseStream},
+    Ident, LitStr, Token, Type,
+};
+
+/// The arguments of a `recap!` invocation
+pub(crate) struct Inline {
+    pattern: LitStr,
+    input: TokenStream2,
+    ty: Type,
+}
+
+impl Parse for Inline {
+    fn parse(stream: ParseStream) -> syn::Result<Self> {
+        let pattern = stream.parse()?;
+        stream.parse::<Token![,]>()?;
+        // the input is any expression up to the `=>`, taken as tokens
+        // so that arbitrary expressions need not be parsed
+        let mut input = TokenStream2::new();
+        while !stream.peek(Token![=>]) {
+            if stream.is_empty() {
+                return Err(stream.error("expected `=> Type` following the input"));
+            }
+            input.extend(Some(stream.parse::<proc_macro2::TokenTree>()?));
+        }
+        if input.is_empty() {
+            return Err(stream.error("expected an input expression"));
+        }
+        stream.parse::<Token![=>]>()?;
+        let ty = stream.parse()?;
+        Ok(Inline { pattern, input, ty })
+    }
+}
+
+pub(crate) fn expand(inline: Inline) -> TokenStream2 {
+    let Inline { pattern, input, ty } = inline;
+    if let Err(err) = Regex::new(&pattern.value()) {
+        return syn::Error::new(
+            pattern.span(),
+            format!("Invalid regular expression provided for `recap!`\n{}", err),
+        )
+        .to_compile_error();
+    }
+    // resolved with mixed site hygiene so neither the static nor the input
+    // binding can be named by, or shadow names used in, the caller's input
+    let re = Ident::new("RE", Span::mixed_site());
+    let value = Ident::new("input", Span::mixed_site());
+    quote! {
+        {
+            extern crate recap;
+            recap::lazy_static! {
+                static ref #re: recap::Regex = recap::Regex::new(#pattern).expect("Failed to compile regex");
+            }
+            let #value = #input;
+            recap::from_captures::<#ty>(&#re, #value)
+        }
+    }
+}
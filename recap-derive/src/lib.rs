@@ -1,42 +1,506 @@
 extern crate proc_macro;
 
+mod format;
+mod impl_deserialize;
+mod impl_from_str;
+mod impl_serialize;
+mod inline;
+mod lint;
+mod module_default;
+mod search;
+
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::quote;
-use regex::Regex;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use regex::{Regex, RegexBuilder};
+use regex_syntax::ast::{Ast, Flag, FlagsItemKind};
 use syn::{
-    parse_macro_input, Data::Struct, DataStruct, DeriveInput, Fields, Ident, Lit, Meta, NestedMeta,
+    ext::IdentExt, parse_macro_input, parse_quote, punctuated::Punctuated, Attribute, Data::Struct,
+    DataStruct, DeriveInput, Field, Fields, GenericArgument, Generics, Lit, Meta, NestedMeta,
+    PathArguments, Token, Type,
 };
 
-#[proc_macro_derive(Recap, attributes(recap))]
+/// Parses some input with a pattern into a type in a single expression,
+/// as in `recap!(r"(?P<n>\d+)", input => Count)`
+#[proc_macro]
+pub fn recap(input: TokenStream) -> TokenStream {
+    inline::expand(parse_macro_input!(input as inline::Inline)).into()
+}
+
+/// Shares a default regex between the types deriving `Recap` among some items,
+/// as in `module_default_regex!(r"(?P<n>\d+)"; struct A { .. } struct B { .. })`
+#[proc_macro]
+pub fn module_default_regex(input: TokenStream) -> TokenStream {
+    module_default::expand(parse_macro_input!(input as module_default::ModuleDefault)).into()
+}
+
+#[proc_macro_derive(Recap, attributes(recap, serde))]
 pub fn derive_recap(item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as DeriveInput);
-    let regex = extract_regex(&item).expect(
-        r#"Unable to resolve recap regex.
-            Make sure your structure has declared an attribute in the form:
-            #[derive(Deserialize, Recap)]
-            #[recap(regex ="your-pattern-here")]
-            struct YourStruct { ... }
-            "#,
-    );
+    expand(&item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    validate(&item, &regex);
+/// Expands a `#[derive(Recap)]`, reporting misuse as an error spanning the
+/// offending attribute option, field or type
+fn expand(item: &DeriveInput) -> syn::Result<TokenStream2> {
+    let metas = get_nested_metas(&item.attrs);
+    if !is_named_struct(item) && newtype_field(item).is_none() && tuple_fields(item).is_none() {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            "Recap regex can only be applied to Structs with named fields, newtypes or tuple structs",
+        ));
+    }
+    validate_options(item)?;
+    let rename_all = rename_all_tokens(item)?;
+    if let Some(format) = get_str_value(&metas, "format") {
+        let error = |message: String| option_error(&metas, "format", &item.ident, message);
+        if get_str_value(&metas, "regex").is_some() {
+            return Err(error(format!(
+                "Recap `format` on `{}` can not be combined with `regex`",
+                item.ident
+            )));
+        }
+        format::to_regex(&format).map_err(|reason| {
+            error(format!(
+                "Invalid recap format for `{}`, which contains {}",
+                item.ident, reason
+            ))
+        })?;
+    }
+    let fallback_regex = extract_fallback_regex(item);
+
+    // patterns built by a function are only known once it is called at runtime,
+    // so they can not be validated nor used to derive pattern dependent methods
+    let regex_fn = get_str_value(&metas, "regex_fn")
+        .map(|name| {
+            if extract_regex(item).is_some()
+                || fallback_regex.is_some()
+                || has_flag(&metas, "serialize")
+                || has_flag(&metas, "anchored")
+                || has_flag(&metas, "lint_literals")
+                || has_flag(&metas, "lint_verbose_whitespace")
+            {
+                return Err(option_error(
+                    &metas,
+                    "regex_fn",
+                    &item.ident,
+                    format!(
+                        "Recap `regex_fn` on `{}` can not be combined with `regex`, `fallback_regex`, `serialize`, `anchored` or lints",
+                        item.ident
+                    ),
+                ));
+            }
+            syn::parse_str::<syn::Path>(&name).map_err(|err| {
+                option_error(
+                    &metas,
+                    "regex_fn",
+                    &item.ident,
+                    format!("Invalid recap regex_fn `{}` for `{}`\n{}", name, item.ident, err),
+                )
+            })
+        })
+        .transpose()?;
+    let newtype = newtype_field(item).is_some();
+    if newtype
+        && (regex_fn.is_some()
+            || fallback_regex.is_some()
+            || extract_field_order(item).is_some()
+            || ["handle_deserialize", "serialize"]
+                .iter()
+                .any(|flag| has_flag(&metas, flag)))
+    {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "Recap newtype `{}` can not be combined with `regex_fn`, `fallback_regex`, `field_order`, `handle_deserialize` or `serialize`",
+                item.ident
+            ),
+        ));
+    }
+    let tuple = tuple_fields(item).is_some();
+    if tuple
+        && (regex_fn.is_some()
+            || fallback_regex.is_some()
+            || get_str_value(&metas, "field_order").is_some()
+            || ["from_str", "handle_deserialize", "serialize"]
+                .iter()
+                .any(|flag| has_flag(&metas, flag)))
+    {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "Recap tuple struct `{}` can not be combined with `regex_fn`, `fallback_regex`, `field_order`, `from_str`, `handle_deserialize` or `serialize`",
+                item.ident
+            ),
+        ));
+    }
+    let regex = match regex_fn {
+        Some(_) => None,
+        None => {
+            let regex = resolve_regex(item)?;
+            validate(item, &regex)?;
+            if let Some(fallback) = &fallback_regex {
+                validate_fallback(item, &regex, fallback)?;
+            }
+            Some(regex)
+        }
+    };
+    let pattern = match &regex {
+        Some(regex) => quote!(#regex),
+        None => quote!(std::convert::AsRef::<str>::as_ref(&#regex_fn())),
+    };
+
+    let options = extract_options_tokens(item, rename_all)?;
+    let handle_deserialize = has_flag(&metas, "handle_deserialize");
+    let bytes = has_flag(&metas, "bytes");
+    let from_str = has_flag(&metas, "from_str");
+    let case_insensitive = has_flag(&metas, "case_insensitive");
+    // ascii patterns are compiled without unicode support, so classes like `\w`
+    // and `\d` match ASCII only and patterns able to match invalid UTF-8 fail to compile
+    let unicode = !has_flag(&metas, "ascii");
+    let trim_newline = has_flag(&metas, "trim_newline");
+    let collapse_whitespace = has_flag(&metas, "collapse_whitespace");
+    if collapse_whitespace && (bytes || item.generics.lifetimes().count() > 0) {
+        return Err(option_error(
+            &metas,
+            "collapse_whitespace",
+            &item.ident,
+            format!(
+                "Recap `collapse_whitespace` on `{}` can not be combined with `bytes` or lifetimes",
+                item.ident
+            ),
+        ));
+    }
+    let mut lints = Vec::new();
+    if let Some(regex) = &regex {
+        if has_flag(&metas, "lint_literals") {
+            lints.extend(literal_field_lints(item, regex));
+        }
+        if has_flag(&metas, "lint_verbose_whitespace") {
+            lints.extend(lint::ignored_whitespace_groups(regex).into_iter().map(|group| {
+                format!(
+                    "recap: whitespace in the named group `{}` of the verbose regex for `{}` is ignored. Did you mean to match it with `\\ ` or `\\s`?",
+                    group, item.ident
+                )
+            }));
+        }
+    }
+    let lints = lint::warnings(item.ident.span(), &lints);
+    if from_str
+        && (handle_deserialize
+            || bytes
+            || item.generics.lifetimes().count() > 0
+            || extract_field_order(item).is_some())
+    {
+        return Err(option_error(
+            &metas,
+            "from_str",
+            &item.ident,
+            format!(
+                "Recap `from_str` on `{}` can not be combined with `handle_deserialize`, `bytes`, `field_order` or lifetimes",
+                item.ident
+            ),
+        ));
+    }
+
+    let pre_process = get_str_value(&metas, "pre_process")
+        .map(|name| {
+            if bytes || item.generics.lifetimes().count() > 0 {
+                return Err(option_error(
+                    &metas,
+                    "pre_process",
+                    &item.ident,
+                    format!(
+                        "Recap `pre_process` on `{}` can not be combined with `bytes` or lifetimes",
+                        item.ident
+                    ),
+                ));
+            }
+            syn::parse_str::<syn::Path>(&name).map_err(|err| {
+                option_error(
+                    &metas,
+                    "pre_process",
+                    &item.ident,
+                    format!(
+                        "Invalid recap pre_process fn `{}` for `{}`\n{}",
+                        name, item.ident, err
+                    ),
+                )
+            })
+        })
+        .transpose()?;
 
     let item_ident = &item.ident;
     let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let parse_generics = with_deserialize_bounds(&item.generics);
+    let (parse_impl_generics, _, parse_where_clause) = parse_generics.split_for_impl();
+
+    // types handling their own deserialization are parsed through their
+    // serde-derived mirror so struct level options still apply
+    let parse_with = |from_captures: TokenStream2, re: TokenStream2| {
+        if handle_deserialize {
+            quote!(#from_captures::<__DeserializeHelper>(&#re, s, &OPTIONS).map(Into::into))
+        } else {
+            quote!(#from_captures(&#re, s, &OPTIONS))
+        }
+    };
+    // a fallback regex is only attempted when the primary regex does not match,
+    // so inputs matching neither report the primary regex's error
+    let with_fallback = |re: TokenStream2,
+                         fallback_re: TokenStream2,
+                         parse: &dyn Fn(TokenStream2) -> syn::Result<TokenStream2>|
+     -> syn::Result<TokenStream2> {
+        let primary = parse(re.clone())?;
+        if fallback_regex.is_some() {
+            let fallback = parse(fallback_re.clone())?;
+            Ok(quote! {
+                if !#re.is_match(s) && #fallback_re.is_match(s) {
+                    #fallback
+                } else {
+                    #primary
+                }
+            })
+        } else {
+            Ok(primary)
+        }
+    };
+    let parse = with_fallback(quote!(RE), quote!(FALLBACK_RE), &|re| {
+        if from_str {
+            impl_from_str::parse_fields(item, &re)
+        } else {
+            Ok(parse_with(quote!(recap::from_captures_with_options), re))
+        }
+    })?;
+    // inputs are scanned for characters outside of the charset right before the
+    // regex is applied, after any other processing of the input
+    let charset = get_str_value(&metas, "charset")
+        .map(|class| {
+            let disallowed = format!("[^{}]", class);
+            if bytes || !class.starts_with('[') || !class.ends_with(']') || Regex::new(&disallowed).is_err() {
+                return Err(option_error(
+                    &metas,
+                    "charset",
+                    &item.ident,
+                    format!(
+                        "Invalid recap charset `{}` for `{}`. Expected a character class such as \"[a-z0-9 ]\", which can not be combined with `bytes`",
+                        class, item.ident
+                    ),
+                ));
+            }
+            Ok(disallowed)
+        })
+        .transpose()?;
+    let parse = if charset.is_some() {
+        quote! {{
+            recap::check_charset(&CHARSET, s)?;
+            #parse
+        }}
+    } else {
+        parse
+    };
+    let impl_deserialize = if handle_deserialize {
+        impl_deserialize::impl_deserialize(item)?
+    } else {
+        quote!()
+    };
+
+    let (static_bytes_re, impl_bytes) = if bytes {
+        let parse_bytes = with_fallback(quote!(BYTES_RE), quote!(FALLBACK_BYTES_RE), &|re| {
+            Ok(parse_with(
+                quote!(recap::from_captures_bytes_with_options),
+                re,
+            ))
+        })?;
+        let parse_bytes = if trim_newline {
+            quote! {{
+                let s = s.strip_suffix(b"\n").map(|s| s.strip_suffix(b"\r").unwrap_or(s)).unwrap_or(s);
+                #parse_bytes
+            }}
+        } else {
+            parse_bytes
+        };
+        let static_fallback_bytes_re = fallback_regex.as_ref().map(|fallback| {
+            quote! {
+                static ref FALLBACK_BYTES_RE: recap::BytesRegex = recap::BytesRegexBuilder::new(#fallback)
+                    .case_insensitive(#case_insensitive)
+                    .unicode(#unicode)
+                    .build()
+                    .expect("Failed to compile regex");
+            }
+        });
+        let lifetimes = item.generics.lifetimes();
+        let also_lifetimes = item.generics.lifetimes();
+        let bytes_lifetimes = item.generics.lifetimes();
+        (
+            quote! {
+                recap::lazy_static! {
+                    static ref BYTES_RE: recap::BytesRegex = recap::BytesRegexBuilder::new(#pattern)
+                        .case_insensitive(#case_insensitive)
+                        .unicode(#unicode)
+                        .build()
+                        .expect("Failed to compile regex");
+                    #static_fallback_bytes_re
+                }
+            },
+            quote! {
+                impl #parse_impl_generics #item_ident #ty_generics #parse_where_clause {
+                    /// Recap derived method. Parses this type from bytes matching
+                    /// the regex associated with this type. Captured values must be valid UTF-8
+                    /// unless they are deserialized as bytes
+                    pub fn try_from_bytes(s: & #(#bytes_lifetimes)* [u8]) -> Result<Self, recap::Error> {
+                        #parse_bytes
+                    }
+                }
+
+                impl #parse_impl_generics std::convert::TryFrom<& #(#lifetimes)* [u8]> for #item_ident #ty_generics #parse_where_clause {
+                    type Error = recap::Error;
+                    fn try_from(s: & #(#also_lifetimes)* [u8]) -> Result<Self, Self::Error> {
+                        Self::try_from_bytes(s)
+                    }
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+
+    let static_fallback_re = fallback_regex.as_ref().map(|fallback| {
+        quote! {
+            static ref FALLBACK_RE: recap::Regex = recap::RegexBuilder::new(#fallback)
+                .case_insensitive(#case_insensitive)
+                .unicode(#unicode)
+                .build()
+                .expect("Failed to compile regex");
+        }
+    });
+    let static_charset = charset.as_ref().map(|disallowed| {
+        quote! {
+            static ref CHARSET: recap::Regex = recap::Regex::new(#disallowed).expect("Failed to compile regex");
+        }
+    });
+    let static_recap_data = quote! {
+        recap::lazy_static! {
+            static ref RE: recap::Regex = recap::RegexBuilder::new(#pattern)
+                .case_insensitive(#case_insensitive)
+                .unicode(#unicode)
+                .build()
+                .expect("Failed to compile regex");
+            static ref OPTIONS: recap::Options = #options;
+            #static_fallback_re
+            #static_charset
+        }
+        #static_bytes_re
+    };
+
+    let (is_match, captures) = if fallback_regex.is_some() {
+        (
+            quote!(RE.is_match(input) || FALLBACK_RE.is_match(input)),
+            quote!(RE.captures(input).or_else(|| FALLBACK_RE.captures(input))),
+        )
+    } else {
+        (quote!(RE.is_match(input)), quote!(RE.captures(input)))
+    };
+    let is_match = if charset.is_some() {
+        quote!(!CHARSET.is_match(input) && (#is_match))
+    } else {
+        is_match
+    };
+    let field_span = match extract_field_order(item) {
+        Some(order) => quote! {
+            let index = [#(#order),*].iter().position(|name| *name == field)?;
+            #captures?.get(index + 1).map(|m| m.range())
+        },
+        None => quote!(#captures?.name(field).map(|m| m.range())),
+    };
+
+    // runs of whitespace are collapsed into an owned string, so types with
+    // lifetimes are not supported
+    let (parse, is_match) = if collapse_whitespace {
+        (
+            quote! {{
+                let collapsed = recap::collapse_whitespace(s);
+                let s: &str = &collapsed;
+                #parse
+            }},
+            quote! {{
+                let collapsed = recap::collapse_whitespace(input);
+                let input: &str = &collapsed;
+                #is_match
+            }},
+        )
+    } else {
+        (parse, is_match)
+    };
+
+    // inputs are pre-processed before any regex is applied. Pre-processors may
+    // return owned strings, which is why types with lifetimes are not supported
+    let (parse, is_match) = match &pre_process {
+        Some(pre_process) => (
+            quote! {{
+                let processed = #pre_process(s);
+                let s: &str = &processed;
+                #parse
+            }},
+            quote! {{
+                let processed = #pre_process(input);
+                let input: &str = &processed;
+                #is_match
+            }},
+        ),
+        None => (parse, is_match),
+    };
+
+    // a single trailing line terminator is stripped before any pre-processing
+    let (parse, is_match) = if trim_newline {
+        (
+            quote! {{
+                let s = s.strip_suffix('\n').map(|s| s.strip_suffix('\r').unwrap_or(s)).unwrap_or(s);
+                #parse
+            }},
+            quote! {{
+                let input = input.strip_suffix('\n').map(|s| s.strip_suffix('\r').unwrap_or(s)).unwrap_or(input);
+                #is_match
+            }},
+        )
+    } else {
+        (parse, is_match)
+    };
 
     let has_lifetimes = item.generics.lifetimes().count() > 0;
     let impl_from_str = if !has_lifetimes {
         quote! {
-            impl #impl_generics std::str::FromStr for #item_ident #ty_generics #where_clause {
+            impl #parse_impl_generics std::str::FromStr for #item_ident #ty_generics #parse_where_clause {
                 type Err = recap::Error;
                 fn from_str(s: &str) -> Result<Self, Self::Err> {
-                    recap::lazy_static! {
-                        static ref RE: recap::Regex = recap::Regex::new(#regex)
-                            .expect("Failed to compile regex");
-                    }
+                    #parse
+                }
+            }
 
-                    recap::from_captures(&RE, s)
+            impl #parse_impl_generics #item_ident #ty_generics #parse_where_clause {
+                /// Recap derived method. Parses some input text, returning `None` rather
+                /// than an error when it does not match the regex associated with this
+                /// type or when any of its captures fail to parse
+                pub fn matched(input: &str) -> Option<Self> {
+                    <Self as std::str::FromStr>::from_str(input).ok()
+                }
+
+                /// Recap derived method. Reads lines from a reader, parsing each which
+                /// matches the regex associated with this type. Lines which do not match
+                /// are skipped. Lines which match but fail to parse are reported as
+                /// `InvalidData` errors, alongside any error reading the next line
+                pub fn from_reader<__R: std::io::BufRead>(
+                    reader: __R,
+                ) -> impl Iterator<Item = std::io::Result<Self>> {
+                    reader.lines().filter_map(|line| match line {
+                        Ok(line) if !Self::is_match(&line) => None,
+                        Ok(line) => Some(
+                            <Self as std::str::FromStr>::from_str(&line)
+                                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+                        ),
+                        Err(e) => Some(Err(e)),
+                    })
                 }
             }
         }
@@ -47,15 +511,10 @@ pub fn derive_recap(item: TokenStream) -> TokenStream {
     let lifetimes = item.generics.lifetimes();
     let also_lifetimes = item.generics.lifetimes();
     let impl_inner = quote! {
-        impl #impl_generics std::convert::TryFrom<& #(#lifetimes)* str> for #item_ident #ty_generics #where_clause {
+        impl #parse_impl_generics std::convert::TryFrom<& #(#lifetimes)* str> for #item_ident #ty_generics #parse_where_clause {
             type Error = recap::Error;
             fn try_from(s: & #(#also_lifetimes)* str) -> Result<Self, Self::Error> {
-                recap::lazy_static! {
-                    static ref RE: recap::Regex = recap::Regex::new(#regex)
-                        .expect("Failed to compile regex");
-                }
-
-                recap::from_captures(&RE, s)
+                #parse
             }
         }
         #impl_from_str
@@ -64,63 +523,804 @@ pub fn derive_recap(item: TokenStream) -> TokenStream {
     let impl_matcher = quote! {
         impl #impl_generics  #item_ident #ty_generics #where_clause {
             /// Recap derived method. Returns true when some input text
-            /// matches the regex associated with this type
+            /// matches the regex, or fallback regex, associated with this type
             pub fn is_match(input: &str) -> bool {
-                recap::lazy_static! {
-                    static ref RE: recap::Regex = recap::Regex::new(#regex)
-                        .expect("Failed to compile regex");
-                }
-                RE.is_match(input)
+                #is_match
+            }
+
+            /// Recap derived method. Returns the compiled regex associated with this type,
+            /// which is compiled once, on first use, and shared with its parsing methods.
+            /// Inputs are matched against any fallback regex separately
+            pub fn regex() -> &'static recap::Regex {
+                &RE
+            }
+
+            /// Recap derived method. Returns the byte range of a single field's
+            /// capture within some input text, or `None` when the input does not
+            /// match the regex associated with this type or the field's capture
+            /// did not participate in the match
+            pub fn field_span(input: &str, field: &str) -> Option<std::ops::Range<usize>> {
+                #field_span
             }
         }
     };
 
-    let injector = Ident::new(
-        &format!("RECAP_IMPL_FOR_{}", item.ident.to_string()),
-        Span::call_site(),
-    );
+    let impl_search_regex = regex
+        .as_ref()
+        .filter(|_| !newtype && !tuple)
+        .map(|regex| search::impl_search_regex(item, regex, case_insensitive, unicode))
+        .transpose()?;
+    let impl_serialize = match &regex {
+        Some(regex) if has_flag(&metas, "serialize") => {
+            impl_serialize::impl_serialize(item, &metas, regex)?
+        }
+        _ => quote!(),
+    };
+
+    let impl_encoded = match get_str_value(&metas, "encoding") {
+        Some(label) => impl_encoded(item, &metas, &label)?,
+        None => quote!(),
+    };
+
+    let impl_json = if cfg!(feature = "json") {
+        quote! {
+            impl #impl_generics #item_ident #ty_generics #where_clause {
+                /// Recap derived method. Converts this value into a `serde_json::Value`.
+                /// Only available when this type implements `serde::Serialize`
+                pub fn to_json_value(&self) -> Result<recap::serde_json::Value, recap::serde_json::Error>
+                where
+                    for<'__recap> &'__recap Self: recap::_serde::Serialize,
+                {
+                    recap::serde_json::to_value(self)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
 
-    let out = quote! {
-        const #injector: () = {
+    Ok(quote! {
+        #[allow(clippy::needless_update)]
+        const _: () = {
             extern crate recap;
+            #static_recap_data
             #impl_inner
             #impl_matcher
+            #impl_search_regex
+            #impl_serialize
+            #impl_bytes
+            #impl_json
+            #impl_encoded
+            #impl_deserialize
+            #lints
         };
+    })
+}
+
+/// Generates `try_from_encoded` for types declaring `#[recap(encoding = "label")]`
+#[cfg(feature = "encoding")]
+fn impl_encoded(
+    item: &DeriveInput,
+    metas: &[Meta],
+    label: &str,
+) -> syn::Result<TokenStream2> {
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+        option_error(
+            metas,
+            "encoding",
+            &item.ident,
+            format!(
+                "Unknown recap encoding `{}` for `{}`. Expected a WHATWG encoding label such as \"windows-1252\" or \"utf-16le\"",
+                label, item.ident
+            ),
+        )
+    })?;
+    if item.generics.lifetimes().count() > 0 {
+        return Err(option_error(
+            metas,
+            "encoding",
+            &item.ident,
+            format!(
+                "Recap `encoding` on `{}` can not be combined with lifetimes",
+                item.ident
+            ),
+        ));
+    }
+    let doc = format!(
+        " Recap derived method. Decodes {} bytes then parses them with the regex\n associated with this type. Fails when the bytes are not valid {}",
+        encoding.name(),
+        encoding.name()
+    );
+    let item_ident = &item.ident;
+    let generics = with_deserialize_bounds(&item.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            #[doc = #doc]
+            pub fn try_from_encoded(bytes: &[u8]) -> Result<Self, recap::Error> {
+                std::str::FromStr::from_str(&recap::decode(bytes, #label)?)
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "encoding"))]
+fn impl_encoded(
+    item: &DeriveInput,
+    metas: &[Meta],
+    _label: &str,
+) -> syn::Result<TokenStream2> {
+    Err(option_error(
+        metas,
+        "encoding",
+        &item.ident,
+        format!(
+            "Recap `encoding` on `{}` requires the recap `encoding` feature",
+            item.ident
+        ),
+    ))
+}
+
+/// Bounds each type parameter of a type's generics by `DeserializeOwned`,
+/// as parsing deserializes each field from borrowed captures
+fn with_deserialize_bounds(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: recap::_serde::de::DeserializeOwned));
+    }
+    generics
+}
+
+/// Resolves warnings for literal text in a pattern which matches a field name
+fn literal_field_lints(
+    item: &DeriveInput,
+    regex: &str,
+) -> Vec<String> {
+    let fields: Vec<String> = match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => fs
+            .named
+            .iter()
+            .filter_map(|field| Some(field.ident.as_ref()?.unraw().to_string()))
+            .collect(),
+        _ => Vec::new(),
     };
+    lint::literal_field_names(regex, &fields)
+        .into_iter()
+        .map(|(literal, field)| {
+            format!(
+                "recap: literal text `{}` in the regex for `{}` matches the field name `{}`. Did you mean to capture it with a named group?",
+                literal, item.ident, field
+            )
+        })
+        .collect()
+}
 
-    out.into()
+/// Compiles a struct level regex the same way generated code does
+fn build_regex(
+    item: &DeriveInput,
+    pattern: &str,
+) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(has_flag(&get_nested_metas(&item.attrs), "case_insensitive"))
+        .unicode(!has_flag(&get_nested_metas(&item.attrs), "ascii"))
+        .build()
+}
+
+/// Resolves the field names of a `#[recap(field_order = "b, a")]` attribute. The
+/// fields of a tuple struct are always ordered by position
+fn extract_field_order(item: &DeriveInput) -> Option<Vec<String>> {
+    if let Some(fields) = tuple_fields(item) {
+        return Some(positional_names(fields));
+    }
+    get_str_value(&get_nested_metas(&item.attrs), "field_order").map(|order| {
+        order
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .collect()
+    })
+}
+
+/// Returns true when a capture group is named for a field. Under
+/// `#[recap(rename_all = "...")]` groups name fields in another case, which the
+/// runtime resolves with `recap::RenameRule::apply`. As this crate can not depend
+/// on recap, names are compared here regardless of case and underscores, the only
+/// parts of a name the supported conventions change
+pub(crate) fn names_field(
+    item: &DeriveInput,
+    group: &str,
+    field: &str,
+) -> bool {
+    if get_str_value(&get_nested_metas(&item.attrs), "rename_all").is_none() {
+        return group == field;
+    }
+    let fold = |name: &str| {
+        name.chars()
+            .filter(|c| *c != '_')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    fold(group) == fold(field)
+}
+
+/// Generates an expression resolving the name of the capture group a field is
+/// deserialized from, applying any `#[recap(rename_all = "...")]` convention
+pub(crate) fn capture_name_tokens(
+    item: &DeriveInput,
+    field: &str,
+) -> syn::Result<TokenStream2> {
+    Ok(match rename_rule(item)? {
+        Some(rule) => quote!(&#rule.apply(#field)),
+        None => quote!(#field),
+    })
+}
+
+/// Resolves the `recap::RenameRule` declared by `#[recap(rename_all = "...")]`
+fn rename_rule(item: &DeriveInput) -> syn::Result<Option<TokenStream2>> {
+    let metas = get_nested_metas(&item.attrs);
+    let rule = match get_str_value(&metas, "rename_all").as_deref() {
+        Some("camelCase") => quote!(recap::RenameRule::CamelCase),
+        Some("PascalCase") => quote!(recap::RenameRule::PascalCase),
+        Some("SCREAMING_SNAKE_CASE") => quote!(recap::RenameRule::ScreamingSnakeCase),
+        Some("kebab-case") => {
+            return Err(option_error(
+                &metas,
+                "rename_all",
+                &item.ident,
+                format!(
+                    "Recap rename_all convention \"kebab-case\" for `{}` names capture groups with `-`, which regex does not accept in group names",
+                    item.ident
+                ),
+            ))
+        }
+        Some(other) => {
+            return Err(option_error(
+                &metas,
+                "rename_all",
+                &item.ident,
+                format!(
+                    "Invalid recap rename_all convention `{}` for `{}`. Expected one of \"camelCase\", \"PascalCase\" or \"SCREAMING_SNAKE_CASE\"",
+                    other, item.ident
+                ),
+            ))
+        }
+        None => return Ok(None),
+    };
+    // the runtime applies the convention to serde's names for fields, so a name
+    // serde renames would no longer be the one validated here
+    let serde_metas = get_serde_metas(&item.attrs);
+    if serde_metas
+        .iter()
+        .any(|meta| meta.path().is_ident("rename_all"))
+    {
+        return Err(option_error(
+            &serde_metas,
+            "rename_all",
+            &item.ident,
+            format!(
+                "Recap `rename_all` on `{}` can not be combined with `#[serde(rename_all)]`",
+                item.ident
+            ),
+        ));
+    }
+    let mut folded: Vec<(String, String)> = Vec::new();
+    for field in named_fields(item).into_iter().flatten() {
+        let name = match &field.ident {
+            Some(ident) => ident.unraw().to_string(),
+            None => continue,
+        };
+        let serde_metas = get_serde_metas(&field.attrs);
+        if serde_metas
+            .iter()
+            .any(|meta| meta.path().is_ident("rename"))
+        {
+            return Err(option_error(
+                &serde_metas,
+                "rename",
+                field,
+                format!(
+                    "Recap `rename_all` on `{}` can not be combined with `#[serde(rename)]` on `{}.{}`",
+                    item.ident, item.ident, name
+                ),
+            ));
+        }
+        if is_skipped(field) {
+            continue;
+        }
+        let fold = name.replace('_', "").to_lowercase();
+        if let Some((other, _)) = folded.iter().find(|(_, folded)| *folded == fold) {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!(
+                    "Recap `rename_all` on `{}` can not tell the fields `{}` and `{}` apart, as they differ only in case and underscores",
+                    item.ident, other, name
+                ),
+            ));
+        }
+        folded.push((name, fold));
+    }
+    Ok(Some(rule))
+}
+
+/// Generates the `recap::RenameRule` declared by `#[recap(rename_all = "...")]`
+fn rename_all_tokens(item: &DeriveInput) -> syn::Result<TokenStream2> {
+    Ok(match rename_rule(item)? {
+        Some(rule) => quote!(Some(#rule)),
+        None => quote!(None),
+    })
+}
+
+fn extract_fallback_regex(item: &DeriveInput) -> Option<String> {
+    get_str_value(&get_nested_metas(&item.attrs), "fallback_regex")
+        .map(|regex| anchored(item, regex))
+}
+
+/// Ensures a fallback regex declares the same named capture groups as the primary regex
+fn validate_fallback(
+    item: &DeriveInput,
+    regex: &str,
+    fallback: &str,
+) -> syn::Result<()> {
+    let metas = get_nested_metas(&item.attrs);
+    let fallback = build_regex(item, fallback).map_err(|err| {
+        option_error(
+            &metas,
+            "fallback_regex",
+            &item.ident,
+            format!(
+                "Invalid fallback regular expression provided for `{}`\n{}",
+                &item.ident, err
+            ),
+        )
+    })?;
+    let names = |re: &Regex| {
+        let mut names: Vec<String> = re.capture_names().flatten().map(String::from).collect();
+        names.sort();
+        names
+    };
+    let primary = build_regex(item, regex).expect("validated regex");
+    if names(&primary) != names(&fallback) {
+        return Err(option_error(
+            &metas,
+            "fallback_regex",
+            &item.ident,
+            format!(
+                "Recap fallback regex for `{}` must declare the same named capture groups as its regex.\n\t\t > Expected {:?} but found {:?}",
+                item.ident,
+                names(&primary),
+                names(&fallback)
+            ),
+        ));
+    }
+    Ok(())
 }
 
 fn validate(
     item: &DeriveInput,
     regex: &str,
-) {
-    let regex = Regex::new(regex).unwrap_or_else(|err| {
-        panic!(
+) -> syn::Result<()> {
+    let metas = get_nested_metas(&item.attrs);
+    let error = |message: String| option_error(&metas, regex_option(item), &item.ident, message);
+    let duplicates = duplicate_group_names(regex);
+    if !duplicates.is_empty() {
+        return Err(error(format!(
+            "Recap regex for `{}` declares the named capture groups `{}` more than once. Each field must be captured by a single named group",
+            item.ident,
+            duplicates.join("`, `")
+        )));
+    }
+    let case_flag = leading_flags(regex)
+        .is_some_and(|(_, flags)| flags.iter().any(|(flag, _)| *flag == Flag::CaseInsensitive));
+    if has_flag(&metas, "case_insensitive") && case_flag {
+        return Err(option_error(
+            &metas,
+            "case_insensitive",
+            &item.ident,
+            format!(
+                "Recap `case_insensitive` on `{}` conflicts with the inline flags its regex opens with. Remove one or the other",
+                item.ident
+            ),
+        ));
+    }
+    let regex = build_regex(item, regex).map_err(|err| {
+        error(format!(
             "Invalid regular expression provided for `{}`\n{}",
             &item.ident, err
+        ))
+    })?;
+    let fields: Vec<String> = match (newtype_field(item), tuple_fields(item)) {
+        (Some(_), _) => vec![newtype_group(item)],
+        (None, Some(fields)) => {
+            let (groups, fields) = (regex.captures_len() - 1, positional_names(fields));
+            if groups != fields.len() {
+                return Err(error(format!(
+                    "Recap regex for tuple struct `{}` must declare a capture group for each of its {} fields, in order, but found {}",
+                    item.ident,
+                    fields.len(),
+                    groups
+                )));
+            }
+            return Ok(());
+        }
+        (None, None) => named_fields(item)?
+            .iter()
+            .filter(|field| !is_skipped(field))
+            .filter_map(|field| Some(field.ident.as_ref()?.unraw().to_string()))
+            .collect(),
+    };
+    // raw companion fields are populated from another field's capture
+    let raw_fields = raw_fields(item);
+    for raw in &raw_fields {
+        if !fields.contains(raw) {
+            return Err(syn::Error::new_spanned(
+                &item.ident,
+                format!(
+                    "Recap `keep_raw` on `{}` names `{}`, which is not a field",
+                    item.ident, raw
+                ),
+            ));
+        }
+    }
+    let group_count = group_count_field(item)?;
+    let rest = rest_field(item)?;
+    let fields: Vec<String> = fields
+        .into_iter()
+        .filter(|field| {
+            !raw_fields.contains(field)
+                && group_count.as_ref() != Some(field)
+                && rest.as_ref() != Some(field)
+        })
+        .collect();
+    if let Some(order) = extract_field_order(item) {
+        let groups = regex.captures_len() - 1;
+        let mut sorted_order = order.clone();
+        sorted_order.sort();
+        let mut sorted_fields = fields.clone();
+        sorted_fields.sort();
+        if sorted_order != sorted_fields || groups != fields.len() {
+            return Err(option_error(
+                &metas,
+                "field_order",
+                &item.ident,
+                format!(
+                    "Recap `field_order` for `{}` must name each of its {} fields once, with a numbered capture group for each.\n\t\t > Found {:?} and {} capture groups",
+                    item.ident,
+                    fields.len(),
+                    order,
+                    groups
+                ),
+            ));
+        }
+        return Ok(());
+    }
+    if get_str_value(&metas, "rename_all").is_some() {
+        if let Some(unknown) = regex
+            .capture_names()
+            .flatten()
+            .find(|name| !fields.iter().any(|field| names_field(item, name, field)))
+        {
+            return Err(error(format!(
+                "Recap capture group `{}` of `{}` does not name a field once `rename_all` is applied.\n\t\t > Expected one of {:?} in the declared case",
+                unknown, item.ident, fields
+            )));
+        }
+    }
+    let (caps, fields) = (regex.capture_names().flatten().count(), fields.len());
+    if caps != fields {
+        return Err(error(format!(
+            "Recap could not derive a `FromStr` impl for `{}`.\n\t\t > Expected regex with {} named capture groups to align with struct fields but found {}",
+            item.ident, fields, caps
+        )));
+    }
+    Ok(())
+}
+
+/// Resolves the name of the field declaring `#[recap(group_count)]`, if any
+fn group_count_field(item: &DeriveInput) -> syn::Result<Option<String>> {
+    let fields = match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => &fs.named,
+        _ => return Ok(None),
+    };
+    let mut counted = fields
+        .iter()
+        .filter(|field| has_flag(&get_nested_metas(&field.attrs), "group_count"));
+    let field = match counted.next() {
+        Some(field) => field,
+        None => return Ok(None),
+    };
+    let name = match &field.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => return Ok(None),
+    };
+    let error = |message: String| {
+        option_error(
+            &get_nested_metas(&field.attrs),
+            "group_count",
+            field,
+            message,
         )
-    });
-    let caps = regex.capture_names().flatten().count();
+    };
+    if counted.next().is_some() || type_name(&field.ty).as_deref() != Some("usize") {
+        return Err(error(format!(
+            "Recap `group_count` option on `{}.{}` requires a usize type and may only be declared by one field",
+            item.ident, name
+        )));
+    }
+    if has_flag(&get_nested_metas(&item.attrs), "from_str") {
+        return Err(error(format!(
+            "Recap `group_count` option on `{}.{}` can not be combined with `from_str`",
+            item.ident, name
+        )));
+    }
+    Ok(Some(name))
+}
+
+/// Resolves the name of the field declaring `#[recap(rest)]`, if any
+fn rest_field(item: &DeriveInput) -> syn::Result<Option<String>> {
     let fields = match &item.data {
         Struct(DataStruct {
             fields: Fields::Named(fs),
             ..
-        }) => fs.named.len(),
-        _ => panic!("Recap regex can only be applied to Structs with named fields"),
+        }) => &fs.named,
+        _ => return Ok(None),
     };
-    if caps != fields {
-        panic!(
-            "Recap could not derive a `FromStr` impl for `{}`.\n\t\t > Expected regex with {} named capture groups to align with struct fields but found {}",
-            item.ident, fields, caps
-        );
+    let mut rests = fields
+        .iter()
+        .filter(|field| has_flag(&get_nested_metas(&field.attrs), "rest"));
+    let field = match rests.next() {
+        Some(field) => field,
+        None => return Ok(None),
+    };
+    let name = match &field.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => return Ok(None),
+    };
+    let error =
+        |message: String| option_error(&get_nested_metas(&field.attrs), "rest", field, message);
+    if rests.next().is_some() || !is_string(&field.ty) {
+        return Err(error(format!(
+            "Recap `rest` option on `{}.{}` requires a string type and may only be declared by one field",
+            item.ident, name
+        )));
+    }
+    let metas = get_nested_metas(&item.attrs);
+    if has_flag(&metas, "from_str") || has_flag(&metas, "serialize") {
+        return Err(error(format!(
+            "Recap `rest` option on `{}.{}` can not be combined with `from_str` or `serialize`",
+            item.ident, name
+        )));
     }
+    Ok(Some(name))
 }
 
-fn extract_regex(item: &DeriveInput) -> Option<String> {
-    item.attrs
+/// Resolves the names of capture groups a pattern declares more than once, in the
+/// order they are first declared. The `regex` crate rejects such patterns with an
+/// error naming a single group, so each group's declarations are scanned for here
+fn duplicate_group_names(pattern: &str) -> Vec<String> {
+    match regex_syntax::ast::parse::Parser::new().parse(pattern) {
+        Err(err)
+            if matches!(
+                err.kind(),
+                regex_syntax::ast::ErrorKind::GroupNameDuplicate { .. }
+            ) => {}
+        _ => return Vec::new(),
+    }
+    let group = Regex::new(r"(\\*)\(\?P?<([A-Za-z_][A-Za-z0-9_.\[\]]*)>").expect("valid regex");
+    let mut seen: Vec<&str> = Vec::new();
+    let mut duplicates = Vec::new();
+    // a group preceded by an odd number of backslashes is an escaped literal
+    for caps in group
+        .captures_iter(pattern)
+        .filter(|caps| caps[1].len() % 2 == 0)
+    {
+        let name = caps.get(2).expect("group name").as_str();
+        if seen.contains(&name) {
+            if !duplicates.iter().any(|duplicate| duplicate == name) {
+                duplicates.push(name.to_string());
+            }
+        } else {
+            seen.push(name);
+        }
+    }
+    duplicates
+}
+
+/// Resolves the inline flags a pattern opens with, such as `(?x-i)`, as the length
+/// of the flag group along with each flag and whether the group sets or clears it
+fn leading_flags(pattern: &str) -> Option<(usize, Vec<(Flag, bool)>)> {
+    let ast = regex_syntax::ast::parse::Parser::new()
+        .parse(pattern)
+        .ok()?;
+    let first = match &ast {
+        Ast::Concat(concat) => concat.asts.first()?,
+        ast => ast,
+    };
+    let flags = match first {
+        Ast::Flags(flags) => flags,
+        _ => return None,
+    };
+    let mut set = true;
+    let mut resolved = Vec::new();
+    for item in &flags.flags.items {
+        match item.kind {
+            FlagsItemKind::Negation => set = false,
+            FlagsItemKind::Flag(flag) => resolved.push((flag, set)),
+        }
+    }
+    Some((flags.span.end.offset, resolved))
+}
+
+/// Resolves the names of fields populated by another field's `keep_raw` option
+fn raw_fields(item: &DeriveInput) -> Vec<String> {
+    match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => fs
+            .named
+            .iter()
+            .filter_map(|field| get_str_value(&get_nested_metas(&field.attrs), "keep_raw"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Resolves the named fields of a struct
+pub(crate) fn named_fields(item: &DeriveInput) -> syn::Result<&Punctuated<Field, Token![,]>> {
+    match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => Ok(&fs.named),
+        _ => Err(syn::Error::new_spanned(
+            &item.ident,
+            "Recap regex can only be applied to Structs with named fields",
+        )),
+    }
+}
+
+fn is_named_struct(item: &DeriveInput) -> bool {
+    named_fields(item).is_ok()
+}
+
+/// Builds an error spanning the `name = ...` or `name` option among some metas,
+/// or some other tokens when the option is not declared
+pub(crate) fn option_error<T: ToTokens + ?Sized>(
+    metas: &[Meta],
+    name: &str,
+    fallback: &T,
+    message: String,
+) -> syn::Error {
+    match metas.iter().find(|meta| meta.path().is_ident(name)) {
+        Some(meta) => syn::Error::new_spanned(meta, message),
+        None => syn::Error::new_spanned(fallback, message),
+    }
+}
+
+/// The options a `#[recap(...)]` attribute on a struct may declare
+const STRUCT_OPTIONS: &[&str] = &[
+    "anchored",
+    "ascii",
+    "bytes",
+    "case_insensitive",
+    "charset",
+    "collapse_whitespace",
+    "default_regex",
+    "encoding",
+    "fallback_regex",
+    "field_order",
+    "format",
+    "from_str",
+    "handle_deserialize",
+    "lint_literals",
+    "lint_verbose_whitespace",
+    "on_missing",
+    "pre_process",
+    "regex",
+    "regex_fn",
+    "rename_all",
+    "serialize",
+    "trim_newline",
+];
+
+/// The options a `#[recap(...)]` attribute on a field may declare
+const FIELD_OPTIONS: &[&str] = &[
+    "accounting",
+    "chunk_width",
+    "counted_by",
+    "date_format",
+    "dedup_whitespace_in_field",
+    "delimiter_regex",
+    "empty_if_missing",
+    "enum_by_discriminant",
+    "err_prefix",
+    "flag_group",
+    "group_count",
+    "iso8601_duration",
+    "keep_raw",
+    "lenient_float",
+    "lines",
+    "map",
+    "map_strict",
+    "missing_as_none",
+    "ok_prefix",
+    "on_missing",
+    "percent",
+    "quote_char",
+    "radix",
+    "range_delimiter",
+    "regex",
+    "rest",
+    "skip",
+    "strip_brackets",
+    "strip_unit",
+    "time_format",
+    "to_nanos",
+    "trim",
+    "trim_end",
+    "trim_start",
+    "utc_offset",
+    "uuid_format",
+    "variant_by",
+    "whitespace_delimited",
+    "width",
+];
+
+/// Rejects options of the struct's and its fields' `#[recap(...)]` attributes
+/// which recap does not know, such as misspellings, rather than ignoring them
+fn validate_options(item: &DeriveInput) -> syn::Result<()> {
+    let unknown = |metas: &[Meta], known: &[&str], owner: String| {
+        metas
+            .iter()
+            .map(|meta| meta.path().to_token_stream().to_string())
+            .find(|option| !known.contains(&option.as_str()))
+            .map_or(Ok(()), |option| {
+                Err(option_error(
+                    metas,
+                    &option,
+                    &item.ident,
+                    format!("Unknown recap option `{}` on `{}`", option, owner),
+                ))
+            })
+    };
+    unknown(
+        &get_nested_metas(&item.attrs),
+        STRUCT_OPTIONS,
+        item.ident.to_string(),
+    )?;
+    if let Struct(DataStruct { fields, .. }) = &item.data {
+        for (index, field) in fields.iter().enumerate() {
+            let name = match &field.ident {
+                Some(ident) => ident.unraw().to_string(),
+                None => index.to_string(),
+            };
+            unknown(
+                &get_nested_metas(&field.attrs),
+                FIELD_OPTIONS,
+                format!("{}.{}", item.ident, name),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Collects the metas nested within any `#[recap(...)]` attributes
+pub(crate) fn get_nested_metas(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs
         .iter()
-        .flat_map(syn::Attribute::parse_meta)
+        .flat_map(Attribute::parse_meta)
         .filter_map(|x| match x {
             Meta::List(y) => Some(y),
             _ => None,
@@ -131,13 +1331,1121 @@ fn extract_regex(item: &DeriveInput) -> Option<String> {
             NestedMeta::Meta(y) => Some(y),
             _ => None,
         })
+        .collect()
+}
+
+/// Resolves the string value of a `name = "value"` meta
+fn get_str_value(
+    metas: &[Meta],
+    name: &str,
+) -> Option<String> {
+    metas
+        .iter()
         .filter_map(|x| match x {
             Meta::NameValue(y) => Some(y),
             _ => None,
         })
-        .find(|x| x.path.is_ident("regex"))
-        .and_then(|x| match x.lit {
+        .find(|x| x.path.is_ident(name))
+        .and_then(|x| match &x.lit {
             Lit::Str(y) => Some(y.value()),
             _ => None,
         })
 }
+
+/// Resolves every value of a `#[recap(name = "...")]` which may be repeated
+fn get_str_values(
+    metas: &[Meta],
+    name: &str,
+) -> Vec<String> {
+    metas
+        .iter()
+        .filter_map(|x| match x {
+            Meta::NameValue(y) if y.path.is_ident(name) => match &y.lit {
+                Lit::Str(y) => Some(y.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn get_char_value(
+    metas: &[Meta],
+    name: &str,
+) -> Option<char> {
+    metas
+        .iter()
+        .filter_map(|x| match x {
+            Meta::NameValue(y) => Some(y),
+            _ => None,
+        })
+        .find(|x| x.path.is_ident(name))
+        .and_then(|x| match &x.lit {
+            Lit::Char(y) => Some(y.value()),
+            _ => None,
+        })
+}
+
+fn get_int_value(
+    metas: &[Meta],
+    name: &str,
+) -> Option<u64> {
+    metas
+        .iter()
+        .filter_map(|x| match x {
+            Meta::NameValue(y) => Some(y),
+            _ => None,
+        })
+        .find(|x| x.path.is_ident(name))
+        .and_then(|x| match &x.lit {
+            Lit::Int(y) => y.base10_parse().ok(),
+            _ => None,
+        })
+}
+
+fn extract_regex(item: &DeriveInput) -> Option<String> {
+    let metas = get_nested_metas(&item.attrs);
+    get_str_value(&metas, "regex").or_else(|| {
+        // formats are validated before any regex is resolved
+        get_str_value(&metas, "format").and_then(|format| format::to_regex(&format).ok())
+    })
+}
+
+/// Resolves a type's own regex, or else the default shared by `module_default_regex!`
+fn extract_regex_or_default(item: &DeriveInput) -> Option<String> {
+    extract_regex(item)
+        .or_else(|| get_str_value(&get_nested_metas(&item.attrs), "default_regex"))
+        .map(|regex| anchored(item, regex))
+}
+
+/// Wraps a pattern as `^(?:...)$` for types declaring `#[recap(anchored)]`, so that
+/// it only matches whole inputs. Inline flags the pattern opens with, such as `(?x)`,
+/// are hoisted in front of the anchor so that they still apply to the whole expression
+fn anchored(
+    item: &DeriveInput,
+    regex: String,
+) -> String {
+    if !has_flag(&get_nested_metas(&item.attrs), "anchored") {
+        return regex;
+    }
+    let (end, flags) = leading_flags(&regex).unwrap_or_default();
+    // a comment in a verbose pattern runs to the end of its line,
+    // which would otherwise swallow the closing anchor
+    let newline = if flags.contains(&(Flag::IgnoreWhitespace, true)) {
+        "\n"
+    } else {
+        ""
+    };
+    format!("{}^(?:{}{})$", &regex[..end], &regex[end..], newline)
+}
+
+/// The name of the option declaring the regex a type resolved, for error spans
+fn regex_option(item: &DeriveInput) -> &'static str {
+    let metas = get_nested_metas(&item.attrs);
+    if get_str_value(&metas, "regex").is_some() {
+        "regex"
+    } else if get_str_value(&metas, "format").is_some() {
+        "format"
+    } else {
+        "default_regex"
+    }
+}
+
+/// The name of the capture group a newtype's value is bound to, which is the
+/// newtype's own name so that errors parsing its value name the type
+pub(crate) fn newtype_group(item: &DeriveInput) -> String {
+    item.ident.unraw().to_string()
+}
+
+/// Resolves the fields of a tuple struct with more than one field, which are
+/// deserialized from its regex's capture groups in order
+pub(crate) fn tuple_fields(item: &DeriveInput) -> Option<&Punctuated<Field, Token![,]>> {
+    match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Unnamed(fs),
+            ..
+        }) if fs.unnamed.len() > 1 => Some(&fs.unnamed),
+        _ => None,
+    }
+}
+
+/// Resolves the names the captures of a tuple struct's fields are bound to, as
+/// their position like `_0`. Skipped fields are not captured
+fn positional_names(fields: &Punctuated<Field, Token![,]>) -> Vec<String> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !is_skipped(field))
+        .map(|(index, _)| format!("_{}", index))
+        .collect()
+}
+
+/// Resolves the field of a tuple struct with a single field
+pub(crate) fn newtype_field(item: &DeriveInput) -> Option<&Field> {
+    match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Unnamed(fs),
+            ..
+        }) if fs.unnamed.len() == 1 => fs.unnamed.first(),
+        _ => None,
+    }
+}
+
+/// Resolves the regex for a type. A struct with a single field may omit named
+/// capture groups from its regex, binding the whole match to its field, or omit
+/// the regex entirely, binding the whole input. A newtype's regex must match
+/// the whole input, which is bound to its value
+fn resolve_regex(item: &DeriveInput) -> syn::Result<String> {
+    if newtype_field(item).is_some() {
+        let regex = extract_regex_or_default(item).ok_or_else(|| {
+            syn::Error::new_spanned(
+                &item.ident,
+                format!(
+                    "Recap newtype `{}` requires a regex validating its value",
+                    item.ident
+                ),
+            )
+        })?;
+        return Ok(format!("^(?P<{}>(?:{}))$", newtype_group(item), regex));
+    }
+    if let Some(regex) = width_regex(item)? {
+        return Ok(regex);
+    }
+    let single_field = match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => {
+            let mut captured = fs.named.iter().filter(|field| !is_skipped(field));
+            match (captured.next(), captured.next()) {
+                (Some(field), None) => field.ident.as_ref().map(|ident| ident.unraw().to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let single_field = single_field.filter(|_| extract_field_order(item).is_none());
+    match (extract_regex_or_default(item), single_field) {
+        (None, Some(field)) => Ok(format!(r"(?s)^(?P<{}>.*)$", field)),
+        (Some(regex), Some(field))
+            if build_regex(item, &regex)
+                .map(|re| re.capture_names().flatten().count() == 0)
+                .unwrap_or_default() =>
+        {
+            Ok(format!("(?P<{}>{})", field, regex))
+        }
+        (Some(regex), _) => Ok(regex),
+        (None, _) => Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "Unable to resolve recap regex for `{}`. Declare one with `#[recap(regex = \"your-pattern-here\")]`",
+                item.ident
+            ),
+        )),
+    }
+}
+
+/// Builds a pattern matching each field, in declaration order, as a run of as many
+/// characters as its `#[recap(width = n)]` attribute declares, for fixed width records
+fn width_regex(item: &DeriveInput) -> syn::Result<Option<String>> {
+    let fields = match &item.data {
+        Struct(DataStruct {
+            fields: Fields::Named(fs),
+            ..
+        }) => &fs.named,
+        _ => return Ok(None),
+    };
+    let raw_fields = raw_fields(item);
+    let group_count = group_count_field(item)?;
+    let rest = rest_field(item)?;
+    let captured: Vec<(String, &Field, Option<u64>)> = fields
+        .iter()
+        .filter(|field| !is_skipped(field))
+        .filter_map(|field| {
+            let name = field.ident.as_ref()?.unraw().to_string();
+            let width = get_int_value(&get_nested_metas(&field.attrs), "width");
+            Some((name, field, width)).filter(|(name, _, _)| {
+                !raw_fields.contains(name)
+                    && group_count.as_ref() != Some(name)
+                    && rest.as_ref() != Some(name)
+            })
+        })
+        .collect();
+    if captured.iter().all(|(_, _, width)| width.is_none()) {
+        return Ok(None);
+    }
+    if extract_regex(item).is_some()
+        || extract_field_order(item).is_some()
+        || get_str_value(&get_nested_metas(&item.attrs), "rename_all").is_some()
+    {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "Recap field `width` attributes on `{}` build its regex and can not be combined with `regex`, `field_order` or `rename_all`",
+                item.ident
+            ),
+        ));
+    }
+    let groups = captured
+        .iter()
+        .map(|(name, field, width)| match width {
+            Some(width) if *width > 0 => {
+                Ok(format!("(?P<{}>.{{{}}})", name, width))
+            }
+            _ => Err(option_error(
+                &get_nested_metas(&field.attrs),
+                "width",
+                *field,
+                format!(
+                    "Recap field `{}.{}` requires a `width` greater than 0, as the regex of `{}` is built from field widths",
+                    item.ident, name, item.ident
+                ),
+            )),
+        })
+        .collect::<syn::Result<String>>()?;
+    Ok(Some(format!("(?s)^{}$", groups)))
+}
+
+fn on_missing_tokens<T: ToTokens + ?Sized>(
+    metas: &[Meta],
+    spanned: &T,
+    policy: &str,
+    context: &dyn std::fmt::Display,
+) -> syn::Result<TokenStream2> {
+    match policy {
+        "error" => Ok(quote!(recap::OnMissing::Error)),
+        "default" => Ok(quote!(recap::OnMissing::Default)),
+        "none" => Ok(quote!(recap::OnMissing::None)),
+        other => Err(option_error(
+            metas,
+            "on_missing",
+            spanned,
+            format!(
+                "Invalid recap on_missing policy `{}` for `{}`. Expected one of \"error\", \"default\" or \"none\"",
+                other, context
+            ),
+        )),
+    }
+}
+
+/// Resolves the policy of a field which resolves to its default when missing. Types
+/// whose default is an empty value are deserialized from one. Others are left out
+/// for serde to default, which requires `#[serde(default)]` on the field or struct
+fn default_policy<T: ToTokens + ?Sized>(
+    item: &DeriveInput,
+    field: &Field,
+    metas: &[Meta],
+    spanned: &T,
+    option: &str,
+    name: &str,
+) -> syn::Result<TokenStream2> {
+    let serde_default = |metas: &[Meta]| {
+        metas.iter().any(|meta| match meta {
+            Meta::Path(path) => path.is_ident("default"),
+            Meta::NameValue(pair) => pair.path.is_ident("default"),
+            _ => false,
+        })
+    };
+    if serde_default(&get_serde_metas(&field.attrs)) || serde_default(&get_serde_metas(&item.attrs))
+    {
+        return Ok(quote!(recap::OnMissing::None));
+    }
+    if has_empty_default(&field.ty) {
+        return Ok(quote!(recap::OnMissing::Default));
+    }
+    Err(option_error(
+        metas,
+        option,
+        spanned,
+        format!(
+            "Recap `{}` resolving `{}.{}` to its default requires `#[serde(default)]` on the field or struct. Only strings, numbers, bool, char, options, sequences and maps default without it",
+            option, item.ident, name
+        ),
+    ))
+}
+
+/// Generates an expression constructing the `recap::Options` declared by
+/// struct and field level `#[recap(...)]` attributes
+fn extract_options_tokens(
+    item: &DeriveInput,
+    rename_all: TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let metas = get_nested_metas(&item.attrs);
+    let on_missing = match get_str_value(&metas, "on_missing") {
+        Some(policy) => on_missing_tokens(&metas, &item.ident, &policy, &item.ident)?,
+        None => quote!(recap::OnMissing::None),
+    };
+
+    let fields: Vec<(String, &Field)> = match (newtype_field(item), tuple_fields(item)) {
+        (Some(field), _) => vec![(newtype_group(item), field)],
+        (None, Some(fields)) => fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (format!("_{}", index), field))
+            .collect(),
+        (None, None) => named_fields(item)?
+            .iter()
+            .filter_map(|field| Some((field.ident.as_ref()?.unraw().to_string(), field)))
+            .collect(),
+    };
+    let mut field_options = Vec::new();
+    for (name, field) in &fields {
+        let assignments = field_options_tokens(item, name, field)?;
+        if assignments.is_empty() {
+            continue;
+        }
+        field_options.push(quote! {
+            fields.insert(
+                #name.to_string(),
+                recap::FieldOptions {
+                    #(#assignments,)*
+                    ..Default::default()
+                },
+            );
+        });
+    }
+
+    let field_order = extract_field_order(item).unwrap_or_default();
+    let group_count = match group_count_field(item)? {
+        Some(field) => quote!(Some(#field.to_string())),
+        None => quote!(None),
+    };
+    let rest = match rest_field(item)? {
+        Some(field) => quote!(Some(#field.to_string())),
+        None => quote!(None),
+    };
+
+    Ok(quote! {
+        recap::Options {
+            on_missing: #on_missing,
+            field_order: vec![#(#field_order.to_string()),*],
+            rename_all: #rename_all,
+            group_count: #group_count,
+            rest: #rest,
+            fields: {
+                #[allow(unused_mut)]
+                let mut fields = std::collections::HashMap::new();
+                #(#field_options)*
+                fields
+            },
+        }
+    })
+}
+
+/// Generates the `recap::FieldOptions` field assignments declared by a field's `#[recap(...)]` attributes
+fn field_options_tokens(
+    item: &DeriveInput,
+    name: &str,
+    field: &Field,
+) -> syn::Result<Vec<TokenStream2>> {
+    let metas = get_nested_metas(&field.attrs);
+    let error = |option: &str, message: String| option_error(&metas, option, field, message);
+    let mut assignments = Vec::new();
+    let field_policy = get_str_value(&metas, "on_missing");
+    if let Some(policy) = &field_policy {
+        let policy = match policy.as_str() {
+            "default" => default_policy(item, field, &metas, field, "on_missing", name)?,
+            _ => on_missing_tokens(&metas, field, policy, &format!("{}.{}", item.ident, name))?,
+        };
+        assignments.push(quote!(on_missing: Some(#policy)));
+    } else if get_str_value(&get_nested_metas(&item.attrs), "on_missing").as_deref()
+        == Some("default")
+        && !has_flag(&metas, "skip")
+        && !has_flag(&metas, "empty_if_missing")
+        && !is_phantom(&field.ty)
+    {
+        // fields without a policy of their own resolve their default as the struct's
+        // policy declares, which only some types may do from an empty value
+        let struct_metas = get_nested_metas(&item.attrs);
+        let policy = default_policy(item, field, &struct_metas, &item.ident, "on_missing", name)?;
+        assignments.push(quote!(on_missing: Some(#policy)));
+    }
+    if has_flag(&metas, "skip") {
+        if get_str_value(&metas, "on_missing").is_some() || has_flag(&metas, "empty_if_missing") {
+            return Err(error(
+                "skip",
+                format!(
+                    "Recap `skip` option on `{}.{}` always resolves to the default and can not be combined with `on_missing` or `empty_if_missing`",
+                    item.ident, name
+                ),
+            ));
+        }
+        // serde supplies `Default::default()` itself for fields it skips
+        let serde_metas = get_serde_metas(&field.attrs);
+        let policy =
+            if has_flag(&serde_metas, "skip") || has_flag(&serde_metas, "skip_deserializing") {
+                quote!(recap::OnMissing::None)
+            } else {
+                default_policy(item, field, &metas, field, "skip", name)?
+            };
+        assignments.push(quote!(on_missing: Some(#policy)));
+    } else if is_phantom(&field.ty) && get_str_value(&metas, "on_missing").is_none() {
+        // an explicit policy on a marker field is pushed above in place of the default
+        assignments.push(quote!(on_missing: Some(recap::OnMissing::Default)));
+    }
+    if has_flag(&metas, "empty_if_missing") {
+        if get_str_value(&metas, "on_missing").is_some() || !is_string(&field.ty) {
+            return Err(error(
+                "empty_if_missing",
+                format!(
+                    "Recap `empty_if_missing` option on `{}.{}` requires a string type and can not be combined with `on_missing`",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(on_missing: Some(recap::OnMissing::Default)));
+    }
+    if has_flag(&metas, "accounting") {
+        if is_unsigned(&field.ty) {
+            return Err(error(
+                "accounting",
+                format!(
+                    "Recap `accounting` option on `{}.{}` requires a signed integer or float type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(accounting: true));
+    }
+    let percent = if has_flag(&metas, "percent") {
+        Some(quote!(recap::Percent::Optional))
+    } else {
+        match get_str_value(&metas, "percent").as_deref() {
+            Some("optional") => Some(quote!(recap::Percent::Optional)),
+            Some("required") => Some(quote!(recap::Percent::Required)),
+            Some(other) => {
+                return Err(error(
+                    "percent",
+                    format!(
+                        "Invalid recap percent policy `{}` for `{}.{}`. Expected one of \"optional\" or \"required\"",
+                        other, item.ident, name
+                    ),
+                ));
+            }
+            None => None,
+        }
+    };
+    if let Some(percent) = percent {
+        if !is_float(&field.ty) {
+            return Err(error(
+                "percent",
+                format!(
+                    "Recap `percent` option on `{}.{}` requires a float type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(percent: Some(#percent)));
+    }
+    let none_values = get_str_values(&metas, "missing_as_none");
+    if !none_values.is_empty() {
+        if !is_option(&field.ty) {
+            return Err(error(
+                "missing_as_none",
+                format!(
+                    "Recap `missing_as_none` option on `{}.{}` requires an `Option` type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(none_values: vec![#(#none_values.to_string()),*]));
+    }
+    if let Some(delimiter) = get_str_value(&metas, "range_delimiter") {
+        assignments.push(quote!(range_delimiter: Some(#delimiter.to_string())));
+    }
+    if let Some(group) = get_str_value(&metas, "variant_by") {
+        assignments.push(quote!(variant_by: Some(#group.to_string())));
+    }
+    if let Some(group) = get_str_value(&metas, "flag_group") {
+        if type_name(&field.ty).as_deref() != Some("bool") || is_option(&field.ty) {
+            return Err(error(
+                "flag_group",
+                format!(
+                    "Recap `flag_group` option on `{}.{}` requires a bool type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(flag_group: Some(#group.to_string())));
+    }
+    if let Some(group) = get_str_value(&metas, "counted_by") {
+        assignments.push(quote!(counted_by: Some(#group.to_string())));
+    }
+    if let Some(raw) = get_str_value(&metas, "keep_raw") {
+        // an optional field deferring to serde leaves its sibling without a value
+        // when its capture did not participate, which only an `Option` resolves
+        let sibling = named_fields(item).into_iter().flatten().find(|sibling| {
+            sibling
+                .ident
+                .as_ref()
+                .is_some_and(|ident| ident.unraw() == raw)
+        });
+        if let Some(sibling) = sibling {
+            let policy = get_str_value(&get_nested_metas(&sibling.attrs), "on_missing")
+                .or_else(|| field_policy.clone())
+                .or_else(|| get_str_value(&get_nested_metas(&item.attrs), "on_missing"));
+            if is_option(&field.ty)
+                && matches!(policy.as_deref(), None | Some("none"))
+                && !is_option(&sibling.ty)
+            {
+                return Err(error(
+                    "keep_raw",
+                    format!(
+                        "Recap `keep_raw` option on the optional field `{}.{}` requires `{}` to be an `Option`, or an `on_missing = \"default\"` policy resolving it to an empty string",
+                        item.ident, name, raw
+                    ),
+                ));
+            }
+        }
+        assignments.push(quote!(keep_raw: Some(#raw.to_string())));
+    }
+    let discriminant = if has_flag(&metas, "enum_by_discriminant") {
+        Some(None)
+    } else {
+        match get_str_value(&metas, "enum_by_discriminant").as_deref() {
+            Some(
+                repr @ ("i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32"
+                | "u64" | "u128" | "usize"),
+            ) => Some(Some(syn::Ident::new(repr, proc_macro2::Span::call_site()))),
+            Some(other) => {
+                return Err(error(
+                    "enum_by_discriminant",
+                    format!(
+                        "Invalid recap enum repr `{}` for `{}.{}`. Expected a primitive integer type such as \"u128\"",
+                        other, item.ident, name
+                    ),
+                ));
+            }
+            None => None,
+        }
+    };
+    if let Some(repr) = discriminant {
+        let ty = impl_from_str::option_inner(&field.ty).unwrap_or(&field.ty);
+        // casting to an i128 keeps the value of any repr narrower than 128 bits.
+        // those of `i128` and `u128` share their bits, so the enum must name which
+        let cast = match repr {
+            Some(repr) => quote!(value as #repr),
+            None => quote! {{
+                const _: () = assert!(
+                    ::core::mem::size_of::<#ty>() < 16,
+                    "Recap `enum_by_discriminant` option on an enum with a 128 bit repr must name it, as in `enum_by_discriminant = \"u128\"`"
+                );
+                value as i128
+            }},
+        };
+        // each variant's discriminant is read by deserializing it from its
+        // name and casting it, which only compiles for fieldless enums
+        assignments.push(quote! {
+            enum_by_discriminant: Some(|variant: &str| {
+                <#ty as recap::_serde::Deserialize>::deserialize(
+                    recap::_serde::de::IntoDeserializer::<recap::_serde::de::value::Error>::into_deserializer(variant),
+                )
+                .ok()
+                .map(|value| recap::Discriminant::from(#cast))
+            })
+        });
+    }
+    match (
+        get_str_value(&metas, "ok_prefix"),
+        get_str_value(&metas, "err_prefix"),
+    ) {
+        (Some(ok), Some(err)) if type_name(&field.ty).as_deref() == Some("Result") => {
+            assignments.push(quote!(result_prefixes: Some((#ok.to_string(), #err.to_string()))));
+        }
+        (None, None) => (),
+        _ => {
+            return Err(error(
+                "ok_prefix",
+                format!(
+                    "Recap `ok_prefix` and `err_prefix` options on `{}.{}` must be declared together on a `Result` type",
+                    item.ident, name
+                ),
+            ));
+        }
+    }
+    if has_flag(&metas, "lenient_float") {
+        if !is_float(&field.ty) {
+            return Err(error(
+                "lenient_float",
+                format!(
+                    "Recap `lenient_float` option on `{}.{}` requires a float type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(lenient_float: true));
+    }
+    if let Some(unit) = get_str_value(&metas, "to_nanos") {
+        if !is_integer(&field.ty) {
+            return Err(error(
+                "to_nanos",
+                format!(
+                    "Recap `to_nanos` option on `{}.{}` requires an integer type",
+                    item.ident, name
+                ),
+            ));
+        }
+        let unit = match unit.as_str() {
+            "secs" => quote!(recap::TimeUnit::Secs),
+            "millis" => quote!(recap::TimeUnit::Millis),
+            "micros" => quote!(recap::TimeUnit::Micros),
+            "nanos" => quote!(recap::TimeUnit::Nanos),
+            other => {
+                return Err(error(
+                    "to_nanos",
+                    format!(
+                        "Invalid recap to_nanos unit `{}` for `{}.{}`. Expected one of \"secs\", \"millis\", \"micros\" or \"nanos\"",
+                        other, item.ident, name
+                    ),
+                ));
+            }
+        };
+        assignments.push(quote!(to_nanos: Some(#unit)));
+    }
+    if has_flag(&metas, "utc_offset") {
+        if !is_integer(&field.ty) || is_unsigned(&field.ty) {
+            return Err(error(
+                "utc_offset",
+                format!(
+                    "Recap `utc_offset` option on `{}.{}` requires a signed integer type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(utc_offset: true));
+    }
+    let units = get_str_values(&metas, "strip_unit");
+    if !units.is_empty() {
+        if !is_numeric(&field.ty) {
+            return Err(error(
+                "strip_unit",
+                format!(
+                    "Recap `strip_unit` option on `{}.{}` requires an integer or float type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(strip_units: vec![#(#units.to_string()),*]));
+    }
+    let entries = get_str_values(&metas, "map");
+    if !entries.is_empty() {
+        let (mut froms, mut tos) = (Vec::new(), Vec::new());
+        for entry in &entries {
+            let (from, to) = match entry.split_once("=>") {
+                Some((from, to)) => (from.trim(), to.trim()),
+                None => {
+                    return Err(error(
+                        "map",
+                        format!(
+                            "Invalid recap map entry `{}` for `{}.{}`. Expected an entry like \"GET => Get\"",
+                            entry, item.ident, name
+                        ),
+                    ));
+                }
+            };
+            if froms.contains(&from) {
+                return Err(error(
+                    "map",
+                    format!(
+                        "Recap map on `{}.{}` declares more than one entry for `{}`",
+                        item.ident, name, from
+                    ),
+                ));
+            }
+            froms.push(from);
+            tos.push(to);
+        }
+        assignments.push(quote! {
+            map: vec![#((#froms.to_string(), #tos.to_string())),*].into_iter().collect()
+        });
+    }
+    if has_flag(&metas, "map_strict") {
+        if entries.is_empty() {
+            return Err(error(
+                "map_strict",
+                format!(
+                    "Recap `map_strict` option on `{}.{}` requires `map` entries",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(map_strict: true));
+    }
+    if let Some(format) = get_str_value(&metas, "uuid_format") {
+        if !cfg!(feature = "uuid") {
+            return Err(error(
+                "uuid_format",
+                format!(
+                    "Recap `uuid_format` option on `{}.{}` requires the recap `uuid` feature",
+                    item.ident, name
+                ),
+            ));
+        }
+        let format = match format.as_str() {
+            "hyphenated" => quote!(recap::UuidFormat::Hyphenated),
+            "simple" => quote!(recap::UuidFormat::Simple),
+            other => {
+                return Err(error(
+                    "uuid_format",
+                    format!(
+                        "Invalid recap uuid_format `{}` for `{}.{}`. Expected one of \"hyphenated\" or \"simple\"",
+                        other, item.ident, name
+                    ),
+                ));
+            }
+        };
+        assignments.push(quote!(uuid_format: Some(#format)));
+    }
+    if has_flag(&metas, "iso8601_duration") {
+        if !cfg!(feature = "iso8601") {
+            return Err(error(
+                "iso8601_duration",
+                format!(
+                    "Recap `iso8601_duration` option on `{}.{}` requires the recap `iso8601` feature",
+                    item.ident, name
+                ),
+            ));
+        }
+        if type_name(&field.ty).as_deref() != Some("Duration") {
+            return Err(error(
+                "iso8601_duration",
+                format!(
+                    "Recap `iso8601_duration` option on `{}.{}` requires a `std::time::Duration` type",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(iso8601_duration: true));
+    }
+    for (option, ty, assignment) in [
+        ("date_format", "NaiveDate", quote!(date_format)),
+        ("time_format", "NaiveTime", quote!(time_format)),
+    ] {
+        if let Some(format) = get_str_value(&metas, option) {
+            if !cfg!(feature = "chrono") {
+                return Err(error(
+                    option,
+                    format!(
+                        "Recap `{}` option on `{}.{}` requires the recap `chrono` feature",
+                        option, item.ident, name
+                    ),
+                ));
+            }
+            if type_name(&field.ty).as_deref() != Some(ty) {
+                return Err(error(
+                    option,
+                    format!(
+                        "Recap `{}` option on `{}.{}` requires a `chrono::{}` type",
+                        option, item.ident, name, ty
+                    ),
+                ));
+            }
+            assignments.push(quote!(#assignment: Some(#format.to_string())));
+        }
+    }
+    // whitespace delimited values are trimmed so that no empty elements are split off
+    if has_flag(&metas, "trim") || has_flag(&metas, "whitespace_delimited") {
+        assignments.push(quote!(trim: true));
+    }
+    if has_flag(&metas, "dedup_whitespace_in_field") {
+        if !is_string(&field.ty) || matches!(field.ty, Type::Reference(_)) {
+            return Err(error(
+                "dedup_whitespace_in_field",
+                format!(
+                    "Recap `dedup_whitespace_in_field` option on `{}.{}` requires an owned string type such as `String` or `Cow<str>`",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(collapse_whitespace: true));
+    }
+    if has_flag(&metas, "trim_start") {
+        assignments.push(quote!(trim_start: true));
+    }
+    if has_flag(&metas, "trim_end") {
+        assignments.push(quote!(trim_end: true));
+    }
+    let delimiter_regex = get_str_value(&metas, "delimiter_regex");
+    if has_flag(&metas, "whitespace_delimited") {
+        if delimiter_regex.is_some() {
+            return Err(error(
+                "whitespace_delimited",
+                format!(
+                    "Recap `whitespace_delimited` option on `{}.{}` can not be combined with `delimiter_regex`",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote! {
+            delimiter_regex: Some(recap::Regex::new(r"\s+").expect("Failed to compile regex"))
+        });
+    }
+    if let Some(delimiter) = delimiter_regex {
+        if let Err(err) = Regex::new(&delimiter) {
+            return Err(error(
+                "delimiter_regex",
+                format!(
+                    "Invalid delimiter regular expression provided for `{}.{}`\n{}",
+                    item.ident, name, err
+                ),
+            ));
+        }
+        assignments.push(quote! {
+            delimiter_regex: Some(recap::Regex::new(#delimiter).expect("Failed to compile regex"))
+        });
+    }
+    let brackets = match get_str_value(&metas, "strip_brackets") {
+        Some(brackets) => Some(brackets),
+        None if has_flag(&metas, "strip_brackets") => Some("[]".to_string()),
+        None => None,
+    };
+    if let Some(brackets) = brackets {
+        let (open, close) = match brackets.as_str() {
+            "[]" => ('[', ']'),
+            "()" => ('(', ')'),
+            "{}" => ('{', '}'),
+            other => {
+                return Err(error(
+                    "strip_brackets",
+                    format!(
+                        "Invalid recap strip_brackets `{}` for `{}.{}`. Expected one of \"[]\", \"()\" or \"{{}}\"",
+                        other, item.ident, name
+                    ),
+                ));
+            }
+        };
+        assignments.push(quote!(strip_brackets: Some((#open, #close))));
+    }
+    if is_byte_vec(&field.ty) {
+        assignments.push(quote!(raw_bytes: true));
+    }
+    if let Some(quote) = get_char_value(&metas, "quote_char") {
+        assignments.push(quote!(quote_char: Some(#quote)));
+    }
+    if has_flag(&metas, "lines") {
+        if get_str_value(&metas, "delimiter_regex").is_some()
+            || has_flag(&metas, "whitespace_delimited")
+            || get_int_value(&metas, "chunk_width").is_some()
+        {
+            return Err(error(
+                "lines",
+                format!(
+                    "Recap `lines` option on `{}.{}` can not be combined with `delimiter_regex`, `whitespace_delimited` or `chunk_width`",
+                    item.ident, name
+                ),
+            ));
+        }
+        assignments.push(quote!(lines: true));
+    }
+    if let Some(width) = get_int_value(&metas, "chunk_width") {
+        if width == 0 || has_flag(&metas, "whitespace_delimited") {
+            return Err(error(
+                "chunk_width",
+                format!(
+                    "Recap `chunk_width` option on `{}.{}` requires a width greater than 0 and can not be combined with `whitespace_delimited`",
+                    item.ident, name
+                ),
+            ));
+        }
+        let width = width as usize;
+        assignments.push(quote!(chunk_width: Some(#width)));
+    }
+    if let Some(radix) = get_int_value(&metas, "radix") {
+        if !(2..=36).contains(&radix) {
+            return Err(error(
+                "radix",
+                format!(
+                    "Invalid recap radix `{}` for `{}.{}`. Expected a radix between 2 and 36",
+                    radix, item.ident, name
+                ),
+            ));
+        }
+        let radix = radix as u32;
+        assignments.push(quote!(radix: Some(#radix)));
+    }
+    if let Some(regex) = get_str_value(&metas, "regex") {
+        if let Err(err) = Regex::new(&regex) {
+            return Err(error(
+                "regex",
+                format!(
+                    "Invalid regular expression provided for `{}.{}`\n{}",
+                    item.ident, name, err
+                ),
+            ));
+        }
+        assignments.push(quote! {
+            regex: Some(recap::Regex::new(#regex).expect("Failed to compile regex"))
+        });
+    }
+    Ok(assignments)
+}
+
+/// Returns true when a field is not deserialized from a capture, as declared by
+/// `#[recap(skip)]`, `#[serde(skip)]` or `#[serde(skip_deserializing)]`, or as
+/// for marker fields whose type path ends in `PhantomData`
+pub(crate) fn is_skipped(field: &Field) -> bool {
+    let serde_metas = get_serde_metas(&field.attrs);
+    is_phantom(&field.ty)
+        || has_flag(&get_nested_metas(&field.attrs), "skip")
+        || has_flag(&serde_metas, "skip")
+        || has_flag(&serde_metas, "skip_deserializing")
+}
+
+/// Resolves the metas nested within `#[serde(...)]` attributes
+pub(crate) fn get_serde_metas(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .flat_map(Attribute::parse_meta)
+        .filter_map(|meta| match meta {
+            Meta::List(list) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|nested| match nested {
+            NestedMeta::Meta(meta) => Some(meta),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns true when a bare `#[recap(name)]` flag is present
+pub(crate) fn has_flag(
+    metas: &[Meta],
+    name: &str,
+) -> bool {
+    metas.iter().any(|x| match x {
+        Meta::Path(path) => path.is_ident(name),
+        _ => false,
+    })
+}
+
+/// Resolves the name of a field's type, looking through `Option<T>`
+fn type_name(ty: &Type) -> Option<String> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident == "Option" {
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                return type_name(inner);
+            }
+        }
+    }
+    Some(segment.ident.to_string())
+}
+
+/// Returns true for `String`, `&str`, `Box<str>` and `Cow<str>` types
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => {
+            matches!(&*reference.elem, Type::Path(path) if path.path.is_ident("str"))
+        }
+        _ => matches!(type_name(ty).as_deref(), Some("String" | "Box" | "Cow")),
+    }
+}
+
+/// Returns true for marker types, detected by a type path ending in `PhantomData`
+/// as the type can not be resolved by a derive. Only the final path segment is
+/// compared, so `std::marker::PhantomData<T>` and a bare `PhantomData<T>` both match,
+/// as does any other type named `PhantomData`, while an alias of it does not
+fn is_phantom(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "PhantomData"),
+        _ => false,
+    }
+}
+
+/// Returns true for types whose default is the value recap deserializes from a
+/// missing capture, which is empty for strings, sequences and maps and zero for numbers
+fn has_empty_default(ty: &Type) -> bool {
+    is_string(ty)
+        || is_numeric(ty)
+        || is_option(ty)
+        || is_phantom(ty)
+        || matches!(
+            type_name(ty).as_deref(),
+            Some(
+                "bool"
+                    | "char"
+                    | "Vec"
+                    | "VecDeque"
+                    | "HashMap"
+                    | "BTreeMap"
+                    | "HashSet"
+                    | "BTreeSet"
+            )
+        )
+}
+
+/// Returns true for `Vec<u8>`, looking through `Option<T>`
+fn is_byte_vec(ty: &Type) -> bool {
+    let ty = impl_from_str::option_inner(ty).unwrap_or(ty);
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    };
+    match segment {
+        Some(segment) if segment.ident == "Vec" => matches!(
+            &segment.arguments,
+            PathArguments::AngleBracketed(args) if matches!(
+                args.args.first(),
+                Some(GenericArgument::Type(Type::Path(inner))) if inner.path.is_ident("u8")
+            )
+        ),
+        _ => false,
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    impl_from_str::option_inner(ty).is_some()
+}
+
+fn is_numeric(ty: &Type) -> bool {
+    is_integer(ty) || is_float(ty)
+}
+
+fn is_float(ty: &Type) -> bool {
+    matches!(type_name(ty).as_deref(), Some("f32" | "f64"))
+}
+
+fn is_integer(ty: &Type) -> bool {
+    is_unsigned(ty)
+        || matches!(
+            type_name(ty).as_deref(),
+            Some("i8" | "i16" | "i32" | "i64" | "i128" | "isize")
+        )
+}
+
+fn is_unsigned(ty: &Type) -> bool {
+    matches!(
+        type_name(ty).as_deref(),
+        Some("u8" | "u16" | "u32" | "u64" | "u128" | "usize")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::names_field;
+
+    #[test]
+    fn names_fields_regardless_of_case_under_rename_all() {
+        // recap's `RenameRule::apply` resolves these names exactly at runtime
+        let item =
+            syn::parse_str("#[recap(rename_all = \"camelCase\")] struct Item { a__b_c: u32 }")
+                .expect("valid item");
+        for group in ["aBC", "ABC", "A__B_C"] {
+            assert!(names_field(&item, group, "a__b_c"));
+        }
+        assert!(!names_field(&item, "aBD", "a__b_c"));
+
+        let item = syn::parse_str("struct Item { a__b_c: u32 }").expect("valid item");
+        assert!(names_field(&item, "a__b_c", "a__b_c"));
+        assert!(!names_field(&item, "aBC", "a__b_c"));
+    }
+}
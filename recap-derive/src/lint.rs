@@ -0,0 +1,183 @@
+//! Opt-in compile time lints for recap patterns
+//!
+//! Stable proc macros can not emit warnings directly, so each lint is surfaced
+//! by referencing a `#[deprecated]` item whose note carries the lint message.
+
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use regex_syntax::hir::{Hir, HirKind};
+
+use crate::search::group_spans;
+
+/// Emits a warning for each message, pointing at the given span
+pub(crate) fn warnings(
+    span: Span,
+    messages: &[String],
+) -> TokenStream2 {
+    let warnings = messages.iter().map(|message| {
+        let lint = Ident::new("RecapLint", span);
+        quote! {
+            const _: () = {
+                #[deprecated(note = #message)]
+                struct RecapLint;
+                let _ = #lint;
+            };
+        }
+    });
+    quote!(#(#warnings)*)
+}
+
+/// Resolves pairs of literal text outside of named capture groups and
+/// field names which that literal text contains as a whole word
+pub(crate) fn literal_field_names(
+    pattern: &str,
+    fields: &[String],
+) -> Vec<(String, String)> {
+    let hir = match regex_syntax::parse(pattern) {
+        Ok(hir) => hir,
+        Err(_) => return Vec::new(),
+    };
+    let mut literals = Vec::new();
+    collect_literals(&hir, &mut literals);
+    let mut matches = Vec::new();
+    for literal in literals {
+        for word in literal.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if fields.iter().any(|field| field == word) {
+                matches.push((literal.clone(), word.to_string()));
+            }
+        }
+    }
+    matches
+}
+
+/// Resolves the names of named groups, in verbose `(?x)` patterns, containing
+/// unescaped whitespace which is ignored rather than matched. Only whitespace
+/// within a character class, or between two pieces of a group on the same line,
+/// is reported as the layout of a verbose pattern is otherwise expected to
+/// include whitespace
+pub(crate) fn ignored_whitespace_groups(pattern: &str) -> Vec<String> {
+    if !is_verbose(pattern) {
+        return Vec::new();
+    }
+    let spans = group_spans(pattern);
+    let mut groups: Vec<String> = Vec::new();
+    for offset in ignored_whitespace(pattern) {
+        // whitespace is reported for the innermost named group containing it
+        let innermost = spans
+            .iter()
+            .filter(|(_, span)| span.contains(&offset))
+            .min_by_key(|(_, span)| span.len());
+        if let Some((name, span)) = innermost {
+            // leading whitespace lays out the group rather than separating pieces
+            if !pattern[span.start..offset].trim().is_empty() && !groups.contains(name) {
+                groups.push(name.clone());
+            }
+        }
+    }
+    groups
+}
+
+/// Detects a flag group enabling the `x` flag
+fn is_verbose(pattern: &str) -> bool {
+    pattern.match_indices("(?").any(|(start, _)| {
+        pattern[start + 2..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .any(|c| c == 'x')
+    })
+}
+
+/// Resolves the offsets of suspicious runs of whitespace in a verbose pattern
+fn ignored_whitespace(pattern: &str) -> Vec<usize> {
+    let chars: Vec<(usize, char)> = pattern.char_indices().collect();
+    let (mut offsets, mut class_depth, mut i) = (Vec::new(), 0, 0);
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        match c {
+            '\\' => i += 1,
+            '[' => class_depth += 1,
+            ']' if class_depth > 0 => class_depth -= 1,
+            '#' if class_depth == 0 => {
+                while i < chars.len() && chars[i].1 != '\n' {
+                    i += 1;
+                }
+            }
+            ' ' | '\t' if class_depth > 0 => offsets.push(offset),
+            ' ' | '\t' => {
+                let before = chars[..i].last().map(|&(_, c)| c);
+                let after = chars[i..]
+                    .iter()
+                    .map(|&(_, c)| c)
+                    .find(|c| *c != ' ' && *c != '\t');
+                let piece = |c: Option<char>| matches!(c, Some(c) if !"|()\n\r".contains(c));
+                if piece(before) && piece(after) {
+                    offsets.push(offset);
+                }
+                while i + 1 < chars.len() && matches!(chars[i + 1].1, ' ' | '\t') {
+                    i += 1;
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    offsets
+}
+
+fn collect_literals(
+    hir: &Hir,
+    literals: &mut Vec<String>,
+) {
+    match hir.kind() {
+        HirKind::Literal(literal) => {
+            literals.push(String::from_utf8_lossy(&literal.0).into_owned());
+        }
+        HirKind::Capture(capture) if capture.name.is_some() => (),
+        HirKind::Capture(capture) => collect_literals(&capture.sub, literals),
+        HirKind::Repetition(repetition) => collect_literals(&repetition.sub, literals),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            for hir in hirs {
+                collect_literals(hir, literals);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ignored_whitespace_groups, literal_field_names};
+
+    #[test]
+    fn finds_field_names_in_literals() {
+        let fields = vec!["a".to_string(), "b".to_string(), "x".to_string()];
+        assert_eq!(
+            literal_field_names(r#"(?P<a>\d+)x(?P<b>\d+)"#, &fields),
+            vec![("x".to_string(), "x".to_string())]
+        );
+        assert_eq!(
+            literal_field_names(r#"(?P<a>\d+) by (?P<b>x\d+)"#, &fields),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn finds_ignored_whitespace_in_verbose_groups() {
+        assert_eq!(
+            ignored_whitespace_groups(
+                r#"(?x)
+                (?P<name>\w+ \w+)  # a comment with spaces
+                \s+
+                (?P<code>
+                    [A-Z ]
+                    | \d+
+                )
+                (?P<ok>\w+\ \w+)
+            "#
+            ),
+            vec!["name".to_string(), "code".to_string()]
+        );
+        assert!(ignored_whitespace_groups(r#"(?x)(?P<name> \w+)"#).is_empty());
+        assert!(ignored_whitespace_groups(r#"(?P<name>\w+ \w+)"#).is_empty());
+    }
+}
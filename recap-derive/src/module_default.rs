@@ -0,0 +1,67 @@
+//! Expands `module_default_regex!("pattern"; items...)` into its items, sharing a
+//! default regex between each of them which derives `Recap`
+//!
+//! The default is passed to each derive as a `#[recap(default_regex = "...")]`
+//! attribute, so it is validated against each type's fields when the type is derived.
+//! A type's own `regex`, `regex_fn` or field `width` attributes take precedence.
+//! Only type definitions may be passed, as each item is parsed as a derive input.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, LitStr, Meta, NestedMeta, Token,
+};
+
+/// The arguments of a `module_default_regex!` invocation
+pub(crate) struct ModuleDefault {
+    pattern: LitStr,
+    items: Vec<DeriveInput>,
+}
+
+impl Parse for ModuleDefault {
+    fn parse(stream: ParseStream) -> syn::Result<Self> {
+        let pattern = stream.parse()?;
+        stream.parse::<Token![;]>()?;
+        let mut items = Vec::new();
+        while !stream.is_empty() {
+            items.push(stream.parse()?);
+        }
+        Ok(ModuleDefault { pattern, items })
+    }
+}
+
+pub(crate) fn expand(module: ModuleDefault) -> TokenStream2 {
+    let ModuleDefault { pattern, items } = module;
+    let items = items.into_iter().map(|mut item| {
+        // `recap` attributes are only known to items deriving `Recap`
+        if derives_recap(&item) {
+            // spanned by the pattern so that errors validating it point at it
+            item.attrs.push(syn::parse_quote_spanned!(pattern.span()=>
+                #[recap(default_regex = #pattern)]
+            ));
+        }
+        item
+    });
+    quote!(#(#items)*)
+}
+
+/// Returns true when an item's `#[derive(...)]` attributes name `Recap`
+fn derives_recap(item: &DeriveInput) -> bool {
+    item.attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            NestedMeta::Meta(meta) => meta
+                .path()
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Recap"),
+            _ => false,
+        })
+}
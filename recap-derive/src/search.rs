@@ -0,0 +1,142 @@
+//! Generates `to_search_regex`, which rebuilds a type's regex to match
+//! records sharing some of a parsed value's field values
+//!
+//! The spans of each named group's sub pattern are resolved at compile time
+//! so that only the selected groups' sub patterns need to be replaced at runtime.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use regex_syntax::ast::{parse::Parser, Ast, GroupKind};
+use std::ops::Range;
+use syn::{ext::IdentExt, DeriveInput};
+
+use crate::impl_from_str::option_inner;
+
+/// Generates the `to_search_regex` method for a type matched by the given pattern
+pub(crate) fn impl_search_regex(
+    item: &DeriveInput,
+    pattern: &str,
+    case_insensitive: bool,
+    unicode: bool,
+) -> syn::Result<TokenStream2> {
+    let fields = crate::named_fields(item)?;
+    let spans = group_spans(pattern);
+
+    let mut bounds = Vec::new();
+    let mut groups = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let field_name = ident.unraw().to_string();
+        let (name, span) = match spans
+            .iter()
+            .find(|(group, _)| crate::names_field(item, group, &field_name))
+        {
+            Some(group) => group,
+            None => continue,
+        };
+        let (start, end) = (span.start, span.end);
+        let (ty, value) = match option_inner(&field.ty) {
+            Some(inner) => (
+                inner,
+                quote!(self.#ident.as_ref().map(|v| std::string::ToString::to_string(&v))),
+            ),
+            None => (
+                &field.ty,
+                quote!(Some(std::string::ToString::to_string(&&self.#ident))),
+            ),
+        };
+        // bounds on references are only checked where the method is called,
+        // so types with fields which are not `Display` still derive
+        bounds.push(quote!(for<'__recap> &'__recap #ty: std::fmt::Display));
+        groups.push((
+            start,
+            quote! {
+                (#start..#end, if fields.contains(&#name) { #value } else { None })
+            },
+        ));
+    }
+    groups.sort_by_key(|(start, _)| *start);
+    let groups = groups.into_iter().map(|(_, group)| group);
+
+    let item_ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let where_clause = match where_clause {
+        Some(clause) => {
+            let predicates = clause.predicates.iter();
+            quote!(where #(#predicates,)* #(#bounds,)*)
+        }
+        None => quote!(where #(#bounds,)*),
+    };
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics {
+            /// Recap derived method. Builds a regex matching records whose named fields
+            /// hold the same values as this one. The sub pattern of each named field's
+            /// capture group is replaced by the field's escaped value, matched literally.
+            /// All other groups keep their sub patterns, acting as wildcards over any
+            /// value they would otherwise match, as do named fields whose value is `None`
+            pub fn to_search_regex(&self, fields: &[&str]) -> recap::Regex
+            #where_clause
+            {
+                let pattern = recap::splice_pattern(#pattern, &[#(#groups,)*]);
+                recap::RegexBuilder::new(&pattern)
+                    .case_insensitive(#case_insensitive)
+                    .unicode(#unicode)
+                    .build()
+                    .expect("Failed to compile regex")
+            }
+        }
+    })
+}
+
+/// Resolves the name and byte span of the sub pattern of each named group in a pattern
+pub(crate) fn group_spans(pattern: &str) -> Vec<(String, Range<usize>)> {
+    let mut spans = Vec::new();
+    if let Ok(ast) = Parser::new().parse(pattern) {
+        collect_group_spans(&ast, &mut spans);
+    }
+    spans
+}
+
+fn collect_group_spans(
+    ast: &Ast,
+    spans: &mut Vec<(String, Range<usize>)>,
+) {
+    match ast {
+        Ast::Group(group) => {
+            if let GroupKind::CaptureName { name, .. } = &group.kind {
+                let span = group.ast.span();
+                spans.push((name.name.clone(), span.start.offset..span.end.offset));
+            }
+            collect_group_spans(&group.ast, spans);
+        }
+        Ast::Repetition(repetition) => collect_group_spans(&repetition.ast, spans),
+        Ast::Concat(concat) => {
+            for ast in &concat.asts {
+                collect_group_spans(ast, spans);
+            }
+        }
+        Ast::Alternation(alternation) => {
+            for ast in &alternation.asts {
+                collect_group_spans(ast, spans);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::group_spans;
+
+    #[test]
+    fn resolves_sub_pattern_spans() {
+        assert_eq!(
+            group_spans(r#"(?P<a>\d+)-(?<b>x(?P<c>y))"#),
+            vec![
+                ("a".to_string(), 6..9),
+                ("b".to_string(), 16..25),
+                ("c".to_string(), 23..24)
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Compares the throughput of parsing a representative log with a unicode
+//! pattern and with the same pattern declared `ascii`
+//!
+//! Run with `cargo run --release --example ascii`
+
+use recap::Recap;
+use serde::Deserialize;
+use std::{error::Error, time::Instant};
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(
+    regex = r#"^(?P<ip>[\d.]+) - (?P<user>\w+) \[(?P<time>[\w:/ +-]+)\] "(?P<method>[A-Z]+) (?P<path>[\w/.-]+)" (?P<status>\d{3}) (?P<bytes>\d+)$"#
+)]
+#[allow(dead_code)]
+struct UnicodeEntry {
+    ip: String,
+    user: String,
+    time: String,
+    method: String,
+    path: String,
+    status: u16,
+    bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(
+    regex = r#"^(?P<ip>[\d.]+) - (?P<user>\w+) \[(?P<time>[\w:/ +-]+)\] "(?P<method>[A-Z]+) (?P<path>[\w/.-]+)" (?P<status>\d{3}) (?P<bytes>\d+)$"#,
+    ascii
+)]
+#[allow(dead_code)]
+struct AsciiEntry {
+    ip: String,
+    user: String,
+    time: String,
+    method: String,
+    path: String,
+    status: u16,
+    bytes: u64,
+}
+
+const LINE: &str =
+    r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif" 200 2326"#;
+const ITERATIONS: u32 = 200_000;
+
+fn time<T>(parse: impl Fn(&str) -> Result<T, recap::Error>) -> Result<f64, Box<dyn Error>> {
+    // parse once up front so lazily compiled regexes are not timed
+    parse(LINE)?;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse(LINE)?;
+    }
+    Ok(ITERATIONS as f64 / start.elapsed().as_secs_f64())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let unicode = time(|line| line.parse::<UnicodeEntry>())?;
+    let ascii = time(|line| line.parse::<AsciiEntry>())?;
+    println!("unicode: {:>12.0} lines/s", unicode);
+    println!("ascii:   {:>12.0} lines/s", ascii);
+    println!("speedup: {:>12.2}x", ascii / unicode);
+
+    Ok(())
+}
@@ -13,7 +13,8 @@
 //! Below is an example that derives a `FromStr` for your type that will
 //! parse into the struct using named capture groups
 //!
-//! ```rust
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
 //! use recap::Recap;
 //! use serde::Deserialize;
 //! use std::error::Error;
@@ -41,7 +42,8 @@
 //!
 //! You can also use Recap with Serde's zero-copy deserialization:
 //!
-//! ```rust
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
 //! use recap::Recap;
 //! use serde::Deserialize;
 //! use std::convert::TryInto;
@@ -106,11 +108,224 @@
 //!   Ok(())
 //! }
 //! ```
-pub use regex::Regex;
+//!
+//! A sequence, such as a `Vec<u32>`, may also be deserialized directly from a pattern
+//! declaring a single named capture group. Its captured value is split into elements
+//! on `,`, as with sequence fields
+//!
+//! ```rust
+//! use recap::{Regex, from_captures};
+//! use std::error::Error;
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let pattern = Regex::new(r#"ids=(?P<ids>\S+)"#)?;
+//!   let ids: Vec<u32> = from_captures(&pattern, "ids=1,2,3")?;
+//!   assert_eq!(ids, vec![1, 2, 3]);
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! Captures may themselves be parsed into nested types. A type declaring
+//! `handle_deserialize` derives its own `Deserialize` impl which parses string
+//! values with its recap regex. A field level `regex` may be used to parse
+//! a field's captured value with a different pattern
+//!
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use recap::Recap;
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! #[derive(Debug, PartialEq, Recap)]
+//! #[recap(regex=r#"(?P<host>[^:]+):(?P<port>\d+)"#, handle_deserialize)]
+//! struct Addr {
+//!   host: String,
+//!   port: u16,
+//! }
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Recap)]
+//! #[recap(regex=r#"(?P<from>\S+)\s(?P<to>\S+)"#)]
+//! struct Route {
+//!   from: Addr,
+//!   #[recap(regex=r#"(?P<port>\d+)@(?P<host>\S+)"#)]
+//!   to: Addr,
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let route: Route = "localhost:80 8080@example.com".parse()?;
+//!   assert_eq!(route.to.port, 8080);
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! A struct with a single field may omit named capture groups from its regex,
+//! in which case the whole match is bound to that field, or omit the regex
+//! entirely, in which case the whole input is
+//!
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use recap::Recap;
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Recap)]
+//! struct Line {
+//!   text: String,
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let line: Line = "hello there".parse()?;
+//!   assert_eq!(line.text, "hello there");
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! `Option` fields resolve to `None` when their capture group did not participate
+//! in a match, and each optional group is resolved on its own. Recap does not infer
+//! dependencies between groups, so a tail of optional groups cascades only as far as
+//! the pattern itself makes it. In `(?P<a>\w+)(?:\s+(?P<b>\w+))?(?:\s+(?P<c>\w+))?`,
+//! `c` can only participate once `b` has, as `b`'s group is tried first and matches
+//! anything `c`'s would. When optional groups match different values, any of them may
+//! participate without the others, and a pattern which should reject a later group
+//! without an earlier one needs to nest it, as in `(?:\s+(?P<b>\d+)(?:\s+(?P<c>\w+))?)?`
+//!
+//! ```rust
+//! use recap::{Regex, from_captures};
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! #[derive(Debug, Deserialize, PartialEq)]
+//! struct Command {
+//!   name: String,
+//!   count: Option<u32>,
+//!   target: Option<String>,
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let pattern = Regex::new(r#"^(?P<name>\w+)(?:\s+(?P<count>\d+))?(?:\s+(?P<target>[a-z]+))?$"#)?;
+//!   let command: Command = from_captures(&pattern, "move north")?;
+//!   assert_eq!(command.count, None);
+//!   assert_eq!(command.target.as_deref(), Some("north"));
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! Tuple fields split their captured value on the field's `delimiter_regex`, `,`
+//! when none is declared, and hold exactly as many elements as their arity. This
+//! suits composite values such as `200/500`, meaning 200 of 500. Quantities derived
+//! from such a pair, like a ratio, are left to methods of the type, which decide how
+//! a zero total resolves
+//!
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use recap::Recap;
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Recap)]
+//! #[recap(regex = r#"^(?P<name>\w+) (?P<usage>\d+/\d+)$"#)]
+//! struct Quota {
+//!   name: String,
+//!   #[recap(delimiter_regex = "/")]
+//!   usage: (u64, u64),
+//! }
+//!
+//! impl Quota {
+//!   /// The share of the total used, or `None` for a zero total
+//!   fn ratio(&self) -> Option<f64> {
+//!     let (used, total) = self.usage;
+//!     (total != 0).then(|| used as f64 / total as f64)
+//!   }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let quota: Quota = "disk 200/500".parse()?;
+//!   assert_eq!(quota.usage, (200, 500));
+//!   assert_eq!(quota.ratio(), Some(0.4));
+//!   assert_eq!("disk 0/0".parse::<Quota>()?.ratio(), None);
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! For one-off parsing, such as in scripts and tests, the `recap!` macro parses
+//! an input with a pattern into any `Deserialize` type in a single expression,
+//! without attributes on the type. The pattern is validated at compile time and
+//! compiled once, on first use, into a static private to the macro's expansion,
+//! so each invocation compiles its own regex. The input expression is evaluated
+//! once and names within it resolve as they would outside of the macro
+//!
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use recap::recap;
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! #[derive(Debug, Deserialize, PartialEq)]
+//! struct Version {
+//!   major: u32,
+//!   minor: u32,
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let version = recap!(r#"(?P<major>\d+)\.(?P<minor>\d+)"#, "1.2" => Version)?;
+//!   assert_eq!(version, Version { major: 1, minor: 2 });
+//!
+//!   Ok(())
+//! }
+//! ```
+//!
+//! Types sharing a pattern may declare it once with the `module_default_regex!` macro,
+//! which takes the pattern followed by type definitions. Each type deriving `Recap`
+//! among them uses the default unless it declares its own `regex`, `regex_fn` or field
+//! `width` attributes, which take precedence. The default is validated against each
+//! type's fields at compile time, as a type's own regex is
+//!
+#![cfg_attr(feature = "derive", doc = "```rust")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use recap::Recap;
+//! use serde::Deserialize;
+//! use std::error::Error;
+//!
+//! recap::module_default_regex! {
+//!   r#"^(?P<key>\w+)=(?P<value>\S+)$"#;
+//!
+//!   #[derive(Debug, Deserialize, Recap)]
+//!   struct Setting {
+//!     key: String,
+//!     value: String,
+//!   }
+//!
+//!   #[derive(Debug, Deserialize, Recap)]
+//!   struct Limit {
+//!     key: String,
+//!     value: u64,
+//!   }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!   let limit: Limit = "files=1024".parse()?;
+//!   assert_eq!(limit.value, 1024);
+//!   assert!(Setting::is_match("mode=fast"));
+//!
+//!   Ok(())
+//! }
+//! ```
+pub use regex::{
+    bytes::{Regex as BytesRegex, RegexBuilder as BytesRegexBuilder},
+    Regex, RegexBuilder,
+};
 use serde::de::{
     self,
     value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer},
-    Deserialize, IntoDeserializer,
+    Deserialize, DeserializeOwned, IntoDeserializer,
+};
+use std::{
+    borrow::Cow, collections::HashMap, fmt::Display, num::ParseFloatError, ops::Range, str::FromStr,
 };
 
 // used in derive crate output
@@ -120,6 +335,15 @@ use serde::de::{
 #[doc(hidden)]
 pub use lazy_static::lazy_static;
 
+// used in derive crate output
+// to reference serde and serde_json
+// without requiring direct dependencies
+#[doc(hidden)]
+pub use serde as _serde;
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub use serde_json;
+
 // Re-export for #[derive(Recap)]
 #[cfg(feature = "derive")]
 #[allow(unused_imports)]
@@ -129,338 +353,4195 @@ extern crate recap_derive;
 #[doc(hidden)]
 pub use recap_derive::*;
 
+#[cfg(feature = "futures")]
+pub mod stream;
+
 /// A type which encapsulates recap errors
 pub type Error = envy::Error;
 type Result<T> = envy::Result<T>;
 
-struct Vars<'a, Iter>(Iter)
-where
-    Iter: IntoIterator<Item = (&'a str, &'a str)>;
-
-struct Val<'a>(&'a str, &'a str);
+/// Policy applied to fields whose capture group did not participate in a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnMissing {
+    /// Fail with a missing value error, even for `Option` fields
+    Error,
+    /// Fill the field with an empty value, which is the default of strings, numbers,
+    /// `bool`, `char`, options, sequences and maps. Other types, such as structs, fail to
+    /// deserialize from one. `#[derive(Recap)]` resolves their defaults through serde,
+    /// requiring `#[serde(default)]` on the field or struct
+    Default,
+    /// Defer to serde, which resolves `Option` fields to `None`
+    /// and fails with a missing value error for anything else
+    #[default]
+    None,
+}
 
-impl<'a: 'de, 'de> IntoDeserializer<'de, Error> for Val<'a> {
-    type Deserializer = Self;
+/// A naming convention of capture names, which are matched with
+/// the `snake_case` names of a struct's fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameRule {
+    /// `requestId` captures the field `request_id`
+    CamelCase,
+    /// `RequestId` captures the field `request_id`
+    PascalCase,
+    /// `REQUEST_ID` captures the field `request_id`
+    ScreamingSnakeCase,
+}
 
-    fn into_deserializer(self) -> Self::Deserializer {
-        self
+impl RenameRule {
+    /// Applies this convention to a `snake_case` field name
+    pub fn apply(
+        self,
+        field: &str,
+    ) -> String {
+        match self {
+            RenameRule::CamelCase | RenameRule::PascalCase => {
+                let mut renamed = String::with_capacity(field.len());
+                let mut upper = self == RenameRule::PascalCase;
+                for c in field.chars() {
+                    if c == '_' {
+                        upper = !renamed.is_empty();
+                    } else if upper {
+                        renamed.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        renamed.push(c);
+                    }
+                }
+                renamed
+            }
+            RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+        }
     }
 }
 
-struct VarName<'a>(&'a str);
+/// The discriminant of a fieldless enum's variant, read in the enum's `#[repr]` type.
+/// Discriminants of any integer type compare by their value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Discriminant(Wide);
 
-impl<'a: 'de, 'de> IntoDeserializer<'de, Error> for VarName<'a> {
-    type Deserializer = Self;
+/// An integer of any primitive type. Values are kept as an `i128` where they fit,
+/// so that each value has a single representation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Wide {
+    Signed(i128),
+    Unsigned(u128),
+}
 
-    fn into_deserializer(self) -> Self::Deserializer {
-        self
+impl Discriminant {
+    /// Parses a captured integer of any primitive type
+    fn parse(value: &str) -> std::result::Result<Self, std::num::ParseIntError> {
+        value
+            .parse::<i128>()
+            .map(Discriminant::from)
+            .or_else(|e| value.parse::<u128>().map(Discriminant::from).map_err(|_| e))
     }
 }
 
-impl<'a, Iter: Iterator<Item = (&'a str, &'a str)>> Iterator for Vars<'a, Iter> {
-    type Item = (VarName<'a>, Val<'a>);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|(k, v)| (VarName(k), Val(k, v)))
+impl Display for Discriminant {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match self.0 {
+            Wide::Signed(value) => Display::fmt(&value, f),
+            Wide::Unsigned(value) => Display::fmt(&value, f),
+        }
     }
 }
 
-macro_rules! forward_parsed_values {
-    ($($ty:ident => $method:ident,)*) => {
+macro_rules! impl_discriminant_from {
+    ($($ty:ident)*) => {
         $(
-            fn $method<V>(self, visitor: V) -> Result<V::Value>
-                where V: de::Visitor<'de>
-            {
-                match self.1.parse::<$ty>() {
-                    Ok(val) => val.into_deserializer().$method(visitor),
-                    Err(e) => Err(de::Error::custom(format_args!("{} while parsing value '{}' provided by {}", e, self.1, self.0)))
+            impl From<$ty> for Discriminant {
+                fn from(value: $ty) -> Self {
+                    match i128::try_from(value) {
+                        Ok(value) => Discriminant(Wide::Signed(value)),
+                        Err(_) => Discriminant(Wide::Unsigned(value as u128)),
+                    }
                 }
             }
         )*
-    }
+    };
 }
 
-impl<'a: 'de, 'de> de::Deserializer<'de> for Val<'a> {
-    type Error = Error;
-    fn deserialize_any<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        BorrowedStrDeserializer::new(self.1).deserialize_any(visitor)
-    }
-
-    fn deserialize_seq<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let values = self.1.split(',').map(|v| Val(self.0, v));
-        SeqDeserializer::new(values).deserialize_seq(visitor)
-    }
+impl_discriminant_from!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
 
-    fn deserialize_option<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_some(self)
-    }
+/// Options which apply to a single field
+#[derive(Debug, Clone, Default)]
+pub struct FieldOptions {
+    /// Overrides the struct-level [`Options::on_missing`] policy for this field.
+    /// The `#[recap(empty_if_missing)]` shorthand sets this to [`OnMissing::Default`]
+    /// so that a string field whose capture did not participate resolves to `""`
+    pub on_missing: Option<OnMissing>,
+    /// Interprets a parenthesized number, as written by accounting formats,
+    /// as negative. `(42)` resolves to `-42`. This only applies to signed integer
+    /// and float fields. Signs within the parentheses are not accepted, so `(-42)`
+    /// and `(+42)` fail to parse while a bare `+42` still resolves to `42`
+    pub accounting: bool,
+    /// Separates the bounds of a `Range` or `RangeInclusive` field's value, `-` when
+    /// this is not provided. Values are split at the first delimiter after their first
+    /// character, so `-5-3` resolves to `-5..3`. Ranges whose start is numerically
+    /// greater than their end fail to parse
+    pub range_delimiter: Option<String>,
+    /// Sentinel values which resolve an `Option` field to `None`, such as `-` or `NaN`
+    /// written in place of a missing measurement. Sentinels are compared with the
+    /// captured value before it is parsed, so a `NaN` sentinel is never parsed as a
+    /// float NaN
+    pub none_values: Vec<String>,
+    /// Names a capture group whose value selects the variant of this enum field,
+    /// in place of a capture named after the field. The group is consumed by this
+    /// field rather than deserialized as a field of its own. Fails with an error
+    /// naming the group when it did not participate in a match
+    pub variant_by: Option<String>,
+    /// Names an optional capture group whose participation in a match sets this
+    /// `bool` field, in place of a capture named after the field. The field is `true`
+    /// when the group participated and `false` otherwise, regardless of what it captured.
+    /// The group is consumed by this field rather than deserialized as a field of its own
+    pub flag_group: Option<String>,
+    /// Names a capture group holding the number of elements this sequence field is
+    /// expected to hold, as in formats which prefix a list with its length like
+    /// `3 a b c`. Parsing fails when the count does not match the number of elements
+    /// the field's value splits into. The group is still deserialized as a field of
+    /// its own when the type declares one
+    pub counted_by: Option<String>,
+    /// Names a sibling `String` field which is populated with this field's captured
+    /// value as it appeared in the input, before any parsing or other field options
+    /// are applied. The sibling field needs no capture group of its own. When this
+    /// field's capture did not participate, the sibling is resolved with this field's
+    /// [`FieldOptions::on_missing`] policy unless it declares one of its own
+    pub keep_raw: Option<String>,
+    /// Resolves the variant of this enum field from a captured integer matched against
+    /// the enum's discriminants, as in `200` for a `#[repr(u16)]` status code enum.
+    /// The function maps the name of each of the enum's variants to its discriminant,
+    /// read in the enum's repr type. `#[derive(Recap)]` requires enums with a 128 bit
+    /// repr to name it, as in `enum_by_discriminant = "u128"`. Integers which are not
+    /// a discriminant fail with an error listing those which are
+    pub enum_by_discriminant: Option<fn(&str) -> Option<Discriminant>>,
+    /// Prefixes marking a `Result` field's value as `Ok` or `Err`, in that order, as in
+    /// `OK:42` and `ERR:timeout`. The prefix is removed and the rest of the value is
+    /// deserialized as the matching variant's type. Values starting with neither fail
+    /// to parse
+    pub result_prefixes: Option<(String, String)>,
+    /// Parses a float field's value ending in `%` as a fraction, so `95%` resolves
+    /// to `0.95`. The [`Percent`] policy determines how values without a `%` are handled
+    pub percent: Option<Percent>,
+    /// Accepts spellings of infinity and NaN in float fields beyond those of
+    /// `f64::from_str`, which already accepts `inf`, `infinity` and `nan` in any
+    /// case with an optional sign. This adds `∞` and the MSVC spellings `1.#INF`,
+    /// `1.#QNAN`, `1.#SNAN` and `1.#IND`, each with an optional sign
+    pub lenient_float: bool,
+    /// A pattern applied to this field's captured value. Its own named captures
+    /// are then deserialized into the field's type, as with a top level struct,
+    /// which allows a single capture to be parsed in two stages.
+    /// A sequence field applies it repeatedly, each match holding one element, so
+    /// that `[a:1][b:2]` resolves to two records. Text between matches is skipped.
+    /// When the field also declares how its value splits, with `delimiter_regex`,
+    /// `quote_char`, `chunk_width` or `lines`, the pattern instead applies to each element
+    pub regex: Option<Regex>,
+    /// Trims leading and trailing whitespace from the captured value
+    pub trim: bool,
+    /// Trims leading whitespace only from the captured value, as for left padded
+    /// columns. Like `trim`, this borrows a subslice of the value and applies before
+    /// any other option, so sentinels, units and delimiters see the trimmed value
+    pub trim_start: bool,
+    /// Trims trailing whitespace only from the captured value, as for right padded
+    /// columns. Like `trim`, this borrows a subslice of the value and applies before
+    /// any other option, so sentinels, units and delimiters see the trimmed value
+    pub trim_end: bool,
+    /// Replaces each run of whitespace within a string field's captured value with a
+    /// single space. A value holding such a run is deserialized from an owned `String`,
+    /// so the field can not borrow it as a `&str` but may as a `Cow<str>`, which is only
+    /// owned when a run was replaced. Values without such runs are still borrowed
+    pub collapse_whitespace: bool,
+    /// A pattern used to split a captured value into the elements of a sequence
+    /// field. Values are split on `,` when this is not provided. Empty strings
+    /// between consecutive delimiters are kept as empty elements, as is the empty string
+    /// following a trailing delimiter. [`from_captures_with_options`] applies this to
+    /// fields of types which do not derive `Recap` as well.
+    /// Sequence fields may be any collection serde deserializes from a sequence,
+    /// such as a `Vec`, `HashSet` or `BTreeSet`. Sets drop duplicate elements.
+    /// With the `smallvec` feature, which enables `smallvec`'s serde support, they may
+    /// also be a `smallvec::SmallVec` such as `SmallVec<[u32; 4]>`, which holds up to
+    /// its inline capacity of elements without allocating. Elements of any of these
+    /// collections are deserialized from the split strings, as scalar fields are.
+    /// Tuple fields are split the same way and must hold exactly as many elements
+    /// as their arity
+    pub delimiter_regex: Option<Regex>,
+    /// A character which quotes sequence elements so that delimiters between a pair
+    /// of them are not split on. `"a,b",c` resolves to the elements `a,b` and `c`.
+    /// This is a minimal quote handler rather than a full RFC 4180 parser: the
+    /// surrounding quotes of an element are removed but escaped quotes within
+    /// an element are left as is
+    pub quote_char: Option<char>,
+    /// Splits a sequence field's value into elements of this many characters, in
+    /// place of splitting on a delimiter, so that `010203` with a width of `2` holds
+    /// the elements `01`, `02` and `03`. Values whose length is not a multiple of the
+    /// width fail to parse
+    pub chunk_width: Option<usize>,
+    /// Splits a sequence field's value into its lines, in place of splitting on a
+    /// delimiter, as for blocks captured with `(?s)`. Lines end with `\n` or `\r\n`.
+    /// Each line is trimmed and lines left empty are dropped
+    pub lines: bool,
+    /// Brackets enclosing a sequence field's value, as in `[1, 2, 3]`, which are
+    /// removed before it is split into elements. Values which are not enclosed in
+    /// them, or in which they are unbalanced, fail to parse
+    pub strip_brackets: Option<(char, char)>,
+    /// Deserializes a field from its captured bytes as is when matched by
+    /// [`from_captures_bytes`], even when they are valid UTF-8, so that a `Vec<u8>`
+    /// holds each captured byte rather than a sequence split from the value.
+    /// `#[derive(Recap)]` sets this for `Vec<u8>` fields
+    pub raw_bytes: bool,
+    /// Parses an integer field's value in the given base, between `2` and `36`,
+    /// so that `ff` with a radix of `16` resolves to `255`. Values are parsed as
+    /// `from_str_radix` would, without a prefix such as `0x`
+    pub radix: Option<u32>,
+    /// Parses a decimal integer field's value as a quantity of the given [`TimeUnit`]
+    /// and stores it as nanoseconds. `1.5` with [`TimeUnit::Millis`] resolves to
+    /// `1500000`. Values which overflow the field's type fail to parse
+    pub to_nanos: Option<TimeUnit>,
+    /// Parses a signed integer field's value as a UTC offset and stores it as seconds
+    /// east of UTC, so `-05:30` resolves to `-19800`. Offsets are written with a sign
+    /// and two digits of hours, optionally followed by two digits of minutes with or
+    /// without a `:`, as in `+00:00`, `-0800` or `+01`. `Z` resolves to `0`
+    pub utc_offset: bool,
+    /// Units stripped from the end of a numeric field's value before it is parsed,
+    /// so that `100px` resolves to `100`. When several units match, the longest
+    /// is stripped. Values of non-numeric fields are left as is
+    pub strip_units: Vec<String>,
+    /// Remaps captured values to canonical ones before they are deserialized, as in
+    /// `GET` to the `Get` variant of an enum field when a log's vocabulary differs
+    /// from a type's serde names. The whole captured value is remapped once it is
+    /// trimmed, before any other option splits or parses it, so this applies to fields
+    /// of any type, including borrowed `&str` fields. Values without an entry are
+    /// deserialized as is, unless [`FieldOptions::map_strict`] is set
+    pub map: HashMap<String, String>,
+    /// Fails to parse values without an entry in the field's [`FieldOptions::map`]
+    pub map_strict: bool,
+    /// Restricts a `uuid::Uuid` field's captured value to a single [`UuidFormat`].
+    /// Any format `uuid` can parse is accepted when this is not provided
+    #[cfg(feature = "uuid")]
+    pub uuid_format: Option<UuidFormat>,
+    /// Parses a `std::time::Duration` field's value as an ISO-8601 duration, such
+    /// as `PT1H30M15S`. Years, months, negative durations and fractions of anything
+    /// but seconds are rejected
+    #[cfg(feature = "iso8601")]
+    pub iso8601_duration: bool,
+    /// Parses a `chrono::NaiveDate` field's value with a `strftime` style format,
+    /// such as `%d/%m/%Y`, rather than chrono's default of `%Y-%m-%d`
+    #[cfg(feature = "chrono")]
+    pub date_format: Option<String>,
+    /// Parses a `chrono::NaiveTime` field's value with a `strftime` style format,
+    /// such as `%I:%M %p`, rather than chrono's default of `%H:%M:%S%.f`
+    #[cfg(feature = "chrono")]
+    pub time_format: Option<String>,
+}
 
-    forward_parsed_values! {
-        bool => deserialize_bool,
-        u8 => deserialize_u8,
-        u16 => deserialize_u16,
-        u32 => deserialize_u32,
-        u64 => deserialize_u64,
-        i8 => deserialize_i8,
-        i16 => deserialize_i16,
-        i32 => deserialize_i32,
-        i64 => deserialize_i64,
-        f32 => deserialize_f32,
-        f64 => deserialize_f64,
+impl FieldOptions {
+    /// Applies value preparing options to the raw captured value of the named field,
+    /// trimming it and then resolving its entry in the field's `map`
+    fn prepare<'v>(
+        &'v self,
+        name: &str,
+        value: &'v str,
+    ) -> Result<&'v str> {
+        let value = match (self.trim || self.trim_start, self.trim || self.trim_end) {
+            (true, true) => value.trim(),
+            (true, false) => value.trim_start(),
+            (false, true) => value.trim_end(),
+            (false, false) => value,
+        };
+        if self.map.is_empty() {
+            return Ok(value);
+        }
+        match self.map.get(value) {
+            Some(mapped) => Ok(mapped),
+            None if self.map_strict => Err(de::Error::custom(format_args!(
+                "unmapped value '{}' provided by {}",
+                value, name
+            ))),
+            None => Ok(value),
+        }
     }
 
-    #[inline]
-    fn deserialize_newtype_struct<V>(
-        self,
-        _: &'static str,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_newtype_struct(self)
+    /// Returns true when this declares how a sequence field's value splits into elements
+    fn splits_explicitly(&self) -> bool {
+        self.delimiter_regex.is_some()
+            || self.quote_char.is_some()
+            || self.chunk_width.is_some()
+            || self.lines
     }
 
-    fn deserialize_enum<V>(
-        self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_enum(self.1.into_deserializer())
+    /// Parses a value with this field's `date_format` or `time_format`, rewriting it
+    /// in the default format chrono's types deserialize from. Values of fields
+    /// without either format resolve to `None`
+    #[cfg(feature = "chrono")]
+    fn reformat_chrono(
+        &self,
+        value: &str,
+    ) -> std::result::Result<Option<String>, chrono::ParseError> {
+        if let Some(format) = &self.date_format {
+            return chrono::NaiveDate::parse_from_str(value, format)
+                .map(|date| Some(date.to_string()));
+        }
+        if let Some(format) = &self.time_format {
+            return chrono::NaiveTime::parse_from_str(value, format)
+                .map(|time| Some(time.to_string()));
+        }
+        Ok(None)
     }
+}
 
-    serde::forward_to_deserialize_any! {
-        char str string unit
-        bytes byte_buf map unit_struct tuple_struct
-        identifier tuple ignored_any
-        struct
-    }
+/// Options which tune how captures are deserialized
+///
+/// These are typically generated from `#[recap(...)]` attributes by `#[derive(Recap)]`
+/// but may also be passed to [`from_captures_with_options`] directly
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Policy applied to any field whose capture group did not participate in a match.
+    /// A field's own [`FieldOptions::on_missing`] takes precedence over this
+    pub on_missing: OnMissing,
+    /// Per-field options keyed by field name. These are looked up by borrowed
+    /// field names while parsing, so lookups do not allocate
+    pub fields: HashMap<String, FieldOptions>,
+    /// Field names bound, in order, to a pattern's numbered capture groups. When
+    /// provided, the first name is bound to group `1`, the second to group `2` and
+    /// so on, and named capture groups are ignored. This is an escape hatch for
+    /// patterns which can not use named groups
+    pub field_order: Vec<String>,
+    /// The naming convention of capture names. When provided, a capture whose name is
+    /// a struct field's name with this convention applied is deserialized as that field.
+    /// This applies to struct fields only, as map keys have no field names to match
+    pub rename_all: Option<RenameRule>,
+    /// Names a `usize` field populated with the number of capture groups which
+    /// participated in a match, not counting the implicit group of the whole match.
+    /// This helps tell apart matches whose optional groups make the set of captured
+    /// fields vary. The field needs no capture group of its own
+    pub group_count: Option<String>,
+    /// Names a field populated with the input which follows the last participating
+    /// capture group of a match, through to the end of the input. When no group
+    /// participated, it follows the whole match instead. This keeps a free-form tail
+    /// after a structured prefix. The field needs no capture group of its own
+    pub rest: Option<String>,
 }
 
-impl<'a: 'de, 'de> de::Deserializer<'de> for VarName<'a> {
-    type Error = Error;
-    fn deserialize_any<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.0.into_deserializer().deserialize_any(visitor)
-    }
+lazy_static::lazy_static! {
+    static ref NO_OPTIONS: Options = Options::default();
+    static ref NO_FIELD_OPTIONS: FieldOptions = FieldOptions::default();
+}
 
-    #[inline]
-    fn deserialize_newtype_struct<V>(
-        self,
-        _: &'static str,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_newtype_struct(self)
+impl Options {
+    fn field(
+        &self,
+        name: &str,
+    ) -> &FieldOptions {
+        self.fields.get(name).unwrap_or(&NO_FIELD_OPTIONS)
     }
 
-    serde::forward_to_deserialize_any! {
-        char str string unit seq option
-        bytes byte_buf map unit_struct tuple_struct
-        identifier tuple ignored_any enum
-        struct bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64
+    /// Resolves a field's policy. A `keep_raw` sibling without a policy of its own
+    /// is missing exactly when the field it keeps the capture of is, so shares its policy
+    fn on_missing(
+        &self,
+        name: &str,
+    ) -> OnMissing {
+        self.field(name)
+            .on_missing
+            .or_else(|| {
+                self.fields
+                    .values()
+                    .find(|options| options.keep_raw.as_deref() == Some(name))?
+                    .on_missing
+            })
+            .unwrap_or(self.on_missing)
     }
 }
 
-/// A deserializer for env vars
-struct Deserializer<'a, 'de: 'a, Iter: Iterator<Item = (&'a str, &'a str)>> {
-    inner: MapDeserializer<'de, Vars<'a, Iter>, Error>,
+/// A single captured value. A `None` value represents a
+/// missing field which should resolve to its default
+struct Val<'a, 'o> {
+    name: &'o str,
+    value: Option<&'a str>,
+    options: &'o FieldOptions,
 }
 
-impl<'a, 'de: 'a, Iter: Iterator<Item = (&'a str, &'a str)>> Deserializer<'a, 'de, Iter> {
-    fn new(vars: Iter) -> Self {
-        Deserializer {
-            inner: MapDeserializer::new(Vars(vars)),
-        }
+impl<'a: 'de, 'de, 'o: 'de> IntoDeserializer<'de, Error> for Val<'a, 'o> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
     }
 }
 
-impl<'a: 'de, 'de, Iter: Iterator<Item = (&'a str, &'a str)>> de::Deserializer<'de>
-    for Deserializer<'a, 'de, Iter>
-{
-    type Error = Error;
-    fn deserialize_any<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.deserialize_map(visitor)
-    }
+struct VarName<'o>(&'o str);
 
-    fn deserialize_map<V>(
-        self,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_map(self.inner)
-    }
+impl<'a: 'de, 'de, 'o: 'de> IntoDeserializer<'de, Error> for Deserializer<'a, 'o> {
+    type Deserializer = Self;
 
-    serde::forward_to_deserialize_any! {
-        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
-        bytes byte_buf unit_struct tuple_struct
-        identifier tuple ignored_any option newtype_struct enum
-        struct
+    fn into_deserializer(self) -> Self {
+        self
     }
 }
 
-/// Deserializes a type based on an iterable of `(&str, &str)`
-/// representing keys and values
-fn from_iter<'a, Iter, T>(iter: Iter) -> Result<T>
-where
-    T: de::Deserialize<'a>,
-    Iter: IntoIterator<Item = (&'a str, &'a str)>,
-{
-    T::deserialize(Deserializer::new(iter.into_iter()))
+impl<'de> IntoDeserializer<'de, Error> for VarName<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// The class of a primitive value parsed from a capture
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Bool,
+    Unsigned,
+    Signed,
+    Float,
+}
+
+/// A primitive type parsed from a captured value
+trait Primitive: Default + FromStr {
+    const KIND: Kind;
+
+    /// Converts an integer, such as a count of nanoseconds, into this
+    /// type, if it's an integer type able to represent it
+    fn from_i128(_value: i128) -> Option<Self> {
+        None
+    }
+
+    /// Converts an `f64` into this type, if it's a float type
+    fn from_f64(_value: f64) -> Option<Self> {
+        None
+    }
+
+    /// Parses a value in the given base, if it's an integer type
+    fn from_str_radix(
+        _value: &str,
+        _radix: u32,
+    ) -> Option<std::result::Result<Self, String>> {
+        None
+    }
+}
+
+macro_rules! impl_primitives {
+    (@integer $($kind:ident: $($ty:ident)*;)*) => {
+        $($(
+            impl Primitive for $ty {
+                const KIND: Kind = Kind::$kind;
+
+                fn from_i128(value: i128) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+
+                fn from_str_radix(value: &str, radix: u32) -> Option<std::result::Result<Self, String>> {
+                    Some(Self::from_str_radix(value, radix).map_err(|e| e.to_string()))
+                }
+            }
+        )*)*
+    };
+    (@float $($ty:ident)*) => {
+        $(
+            impl Primitive for $ty {
+                const KIND: Kind = Kind::Float;
+
+                fn from_f64(value: f64) -> Option<Self> {
+                    Some(value as Self)
+                }
+            }
+        )*
+    };
+    ($($kind:ident: $($ty:ident)*;)*) => {
+        $($(
+            impl Primitive for $ty {
+                const KIND: Kind = Kind::$kind;
+            }
+        )*)*
+    };
+}
+
+impl_primitives! {
+    @integer
+    Unsigned: u8 u16 u32 u64 u128;
+    Signed: i8 i16 i32 i64 i128;
+}
+
+impl_primitives! {
+    Bool: bool;
+}
+
+impl_primitives! {
+    @float f32 f64
+}
+
+/// A textual format of a `uuid::Uuid`
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UuidFormat {
+    /// Hyphenated groups of hex digits, as in `550e8400-e29b-41d4-a716-446655440000`
+    Hyphenated,
+    /// Hex digits without hyphens, as in `550e8400e29b41d4a716446655440000`
+    Simple,
+}
+
+#[cfg(feature = "uuid")]
+impl UuidFormat {
+    /// Checks that a value is a uuid written in this format
+    fn check(
+        self,
+        value: &str,
+    ) -> std::result::Result<(), String> {
+        let (len, name) = match self {
+            UuidFormat::Hyphenated => (36, "hyphenated"),
+            UuidFormat::Simple => (32, "simple"),
+        };
+        if value.len() == len && uuid::Uuid::try_parse(value).is_ok() {
+            Ok(())
+        } else {
+            Err(format!("expected a {} uuid", name))
+        }
+    }
+}
+
+/// How a `percent` field handles values without a trailing `%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Percent {
+    /// Values without a trailing `%` are parsed as is, so `0.5` resolves to `0.5`
+    Optional,
+    /// Values without a trailing `%` fail to parse
+    Required,
+}
+
+/// A unit of time used to scale captured values into nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Seconds
+    Secs,
+    /// Milliseconds
+    Millis,
+    /// Microseconds
+    Micros,
+    /// Nanoseconds
+    Nanos,
+}
+
+impl TimeUnit {
+    fn nanos(self) -> i128 {
+        match self {
+            TimeUnit::Secs => 1_000_000_000,
+            TimeUnit::Millis => 1_000_000,
+            TimeUnit::Micros => 1_000,
+            TimeUnit::Nanos => 1,
+        }
+    }
+
+    /// Parses a possibly signed decimal value in this unit into nanoseconds.
+    /// Fractional digits finer than a nanosecond are truncated
+    fn to_nanos(
+        self,
+        value: &str,
+    ) -> std::result::Result<i128, String> {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err("invalid digit found in string".into());
+        }
+        let overflow = || "number too large to represent in nanoseconds".to_string();
+        let whole: i128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| overflow())?
+        };
+        let mut nanos = whole.checked_mul(self.nanos()).ok_or_else(overflow)?;
+        let mut place = self.nanos();
+        for digit in fraction.bytes() {
+            place /= 10;
+            if place == 0 {
+                break;
+            }
+            nanos = nanos
+                .checked_add(i128::from(digit - b'0') * place)
+                .ok_or_else(overflow)?;
+        }
+        Ok(if negative { -nanos } else { nanos })
+    }
+}
+
+/// Parses a signed UTC offset such as `+05:30`, `-0800` or `+01`, or `Z`,
+/// into seconds east of UTC
+fn parse_utc_offset(value: &str) -> std::result::Result<i32, String> {
+    if value == "Z" {
+        return Ok(0);
+    }
+    let invalid = || {
+        format!(
+            "expected a UTC offset such as +05:30 or -08:00 but found '{}'",
+            value
+        )
+    };
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').ok_or_else(invalid)?),
+    };
+    let (hours, minutes) = match (rest.len(), rest.split_once(':')) {
+        (_, Some((hours, minutes))) => (hours, minutes),
+        (4, None) => rest.split_at(2),
+        (2, None) => (rest, "00"),
+        _ => return Err(invalid()),
+    };
+    if hours.len() != 2
+        || minutes.len() != 2
+        || !rest.bytes().all(|b| b.is_ascii_digit() || b == b':')
+    {
+        return Err(invalid());
+    }
+    let (hours, minutes): (i32, i32) = (
+        hours.parse().map_err(|_| invalid())?,
+        minutes.parse().map_err(|_| invalid())?,
+    );
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parses an ISO-8601 duration such as `PT1H30M15S` or `P1DT12H`
+///
+/// Weeks, days, hours, minutes and seconds are supported, with days taken to be
+/// 24 hours. Years and months are rejected as their length depends on a calendar,
+/// as are negative durations. Only seconds may be fractional, with up to nanosecond
+/// precision, using either `.` or `,` as the decimal mark
+#[cfg(feature = "iso8601")]
+fn parse_iso8601_duration(value: &str) -> std::result::Result<std::time::Duration, String> {
+    let invalid = || "invalid ISO-8601 duration".to_string();
+    let rest = value.strip_prefix('P').ok_or_else(invalid)?;
+    let (date, time) = match rest.split_once('T') {
+        Some((_, "")) => return Err(invalid()),
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return Err(invalid());
+    }
+    let overflow = || "ISO-8601 duration too large to represent".to_string();
+    let (mut secs, mut nanos) = (0u64, 0u32);
+    for (mut part, designators) in [(date, "YMWD"), (time.unwrap_or_default(), "HMS")] {
+        // designators must appear at most once and in order
+        let mut allowed = designators;
+        while !part.is_empty() {
+            let end = part
+                .find(|c: char| !c.is_ascii_digit() && c != '.' && c != ',')
+                .ok_or_else(invalid)?;
+            let number = &part[..end];
+            let designator = part[end..].chars().next().expect("designator");
+            part = &part[end + designator.len_utf8()..];
+            let position = allowed.find(designator).ok_or_else(invalid)?;
+            allowed = &allowed[position + 1..];
+            let (unit, name) = match (designators, designator) {
+                ("YMWD", 'Y') => return Err("years are not supported in ISO-8601 durations".into()),
+                ("YMWD", 'M') => {
+                    return Err("months are not supported in ISO-8601 durations".into())
+                }
+                (_, 'W') => (7 * 24 * 60 * 60, "weeks"),
+                (_, 'D') => (24 * 60 * 60, "days"),
+                (_, 'H') => (60 * 60, "hours"),
+                (_, 'M') => (60, "minutes"),
+                _ => (1, "seconds"),
+            };
+            let (whole, fraction) = match number.split_once(['.', ',']) {
+                Some(_) if designator != 'S' => {
+                    return Err(format!(
+                        "fractional {} are not supported in ISO-8601 durations",
+                        name
+                    ))
+                }
+                Some((whole, fraction)) => (whole, fraction),
+                None => (number, ""),
+            };
+            if whole.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            let whole: u64 = whole.parse().map_err(|_| overflow())?;
+            secs = whole
+                .checked_mul(unit)
+                .and_then(|component| secs.checked_add(component))
+                .ok_or_else(overflow)?;
+            let mut place = 100_000_000;
+            for digit in fraction.bytes().take(9) {
+                nanos += u32::from(digit - b'0') * place;
+                place /= 10;
+            }
+        }
+    }
+    Ok(std::time::Duration::new(secs, nanos))
+}
+
+impl<'a, 'o> Val<'a, 'o> {
+    /// Resolves a deserializer over the captures of this field's own regex, if any
+    fn nested(&self) -> Option<Result<Deserializer<'a, 'o>>> {
+        let re = self.options.regex.as_ref()?;
+        let value = self.value?;
+        Some(
+            captures_deserializer(re, value, &NO_OPTIONS)
+                .map_err(|e| de::Error::custom(format_args!("{} provided by {}", e, self.name))),
+        )
+    }
+
+    /// Resolves the name of the variant whose discriminant is this value's integer
+    fn variant_by_discriminant(
+        &self,
+        value: &str,
+        variants: &'static [&'static str],
+        discriminant: fn(&str) -> Option<Discriminant>,
+    ) -> Result<&'static str> {
+        let number = Discriminant::parse(value).map_err(|e| {
+            de::Error::custom(format_args!(
+                "{} while parsing discriminant '{}' provided by {}",
+                e, value, self.name
+            ))
+        })?;
+        let mut valid = Vec::with_capacity(variants.len());
+        for variant in variants {
+            match discriminant(variant) {
+                Some(d) if d == number => return Ok(variant),
+                Some(d) => valid.push(d),
+                None => (),
+            }
+        }
+        valid.sort_unstable();
+        let valid = valid.iter().map(ToString::to_string).collect::<Vec<_>>();
+        Err(de::Error::custom(format_args!(
+            "discriminant {} provided by {} is out of range, expected one of {}",
+            number,
+            self.name,
+            valid.join(", ")
+        )))
+    }
+
+    /// Splits this value into the bounds of a range, when deserializing a `Range`
+    /// or `RangeInclusive`
+    fn range_bounds(
+        &self,
+        name: &str,
+        fields: &[&str],
+    ) -> Option<Result<(&'a str, &'a str)>> {
+        if !matches!(name, "Range" | "RangeInclusive") || fields != ["start", "end"] {
+            return None;
+        }
+        let value = self.value?;
+        let delimiter = self.options.range_delimiter.as_deref().unwrap_or("-");
+        // skip the first character so that a negative start is kept whole
+        let first = value.chars().next().map_or(0, char::len_utf8);
+        let bounds = value[first..]
+            .find(delimiter)
+            .map(|index| {
+                (
+                    &value[..first + index],
+                    &value[first + index + delimiter.len()..],
+                )
+            })
+            .ok_or_else(|| {
+                de::Error::custom(format_args!(
+                    "expected a range delimited by '{}' in value '{}' provided by {}",
+                    delimiter, value, self.name
+                ))
+            });
+        Some(bounds.and_then(
+            |(start, end)| match (start.parse::<f64>(), end.parse::<f64>()) {
+                (Ok(from), Ok(to)) if from > to => Err(de::Error::custom(format_args!(
+                    "range start {} is greater than its end {} in value provided by {}",
+                    start, end, self.name
+                ))),
+                _ => Ok((start, end)),
+            },
+        ))
+    }
+
+    /// Splits this value into the elements of a sequence
+    fn elements(&self) -> Result<Vec<&'a str>> {
+        let value = match self.value {
+            // a value trimmed down to nothing holds no elements
+            Some("") if self.options.trim || self.options.trim_start || self.options.trim_end => {
+                return Ok(Vec::new())
+            }
+            Some(value) => value,
+            None => return Ok(Vec::new()),
+        };
+        let value = match self.options.strip_brackets {
+            Some(brackets) => match self.unbracket(value, brackets)? {
+                "" => return Ok(Vec::new()),
+                inner => inner,
+            },
+            None => value,
+        };
+        if let Some(width) = self.options.chunk_width {
+            return self.chunks(value, width);
+        }
+        if self.options.lines {
+            return Ok(value
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect());
+        }
+        let quote = match self.options.quote_char {
+            Some(quote) => quote,
+            None => {
+                return Ok(match &self.options.delimiter_regex {
+                    Some(delimiter) => delimiter.split(value).collect(),
+                    None => value.split(',').collect(),
+                })
+            }
+        };
+        let delimiters: Box<dyn Iterator<Item = (usize, usize)>> = match &self
+            .options
+            .delimiter_regex
+        {
+            Some(delimiter) => Box::new(delimiter.find_iter(value).map(|m| (m.start(), m.end()))),
+            None => Box::new(
+                value
+                    .match_indices(',')
+                    .map(|(start, _)| (start, start + 1)),
+            ),
+        };
+        let unquote = |element: &'a str| {
+            element
+                .strip_prefix(quote)
+                .and_then(|element| element.strip_suffix(quote))
+                .unwrap_or(element)
+        };
+        let (mut elements, mut start, mut scanned, mut quotes) = (Vec::new(), 0, 0, 0);
+        for (delimiter_start, delimiter_end) in delimiters {
+            quotes += value[scanned..delimiter_start].matches(quote).count();
+            scanned = delimiter_start;
+            // delimiters within an open quote belong to the element
+            if quotes % 2 == 0 {
+                elements.push(unquote(&value[start..delimiter_start]));
+                start = delimiter_end;
+            }
+        }
+        elements.push(unquote(&value[start..]));
+        Ok(elements)
+    }
+
+    /// Removes the brackets enclosing a value, ensuring those within it are balanced
+    fn unbracket(
+        &self,
+        value: &'a str,
+        (open, close): (char, char),
+    ) -> Result<&'a str> {
+        let inner = value
+            .strip_prefix(open)
+            .and_then(|value| value.strip_suffix(close));
+        let balanced = inner.is_some_and(|inner| {
+            let mut depth = 0usize;
+            inner.chars().all(|c| {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    match depth.checked_sub(1) {
+                        Some(remaining) => depth = remaining,
+                        None => return false,
+                    }
+                }
+                true
+            }) && depth == 0
+        });
+        match inner {
+            Some(inner) if balanced => Ok(inner),
+            _ => Err(de::Error::custom(format_args!(
+                "expected value '{}' provided by {} to be enclosed in balanced '{}' and '{}'",
+                value, self.name, open, close
+            ))),
+        }
+    }
+
+    /// Splits a value into elements of a fixed number of characters
+    fn chunks(
+        &self,
+        value: &'a str,
+        width: usize,
+    ) -> Result<Vec<&'a str>> {
+        let boundaries: Vec<usize> = value
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(Some(value.len()))
+            .step_by(width.max(1))
+            .collect();
+        if width == 0 || *boundaries.last().unwrap_or(&0) != value.len() {
+            return Err(de::Error::custom(format_args!(
+                "expected a length which is a multiple of {} in value '{}' provided by {}",
+                width, value, self.name
+            )));
+        }
+        Ok(boundaries
+            .windows(2)
+            .map(|bounds| &value[bounds[0]..bounds[1]])
+            .collect())
+    }
+
+    /// Parses a raw value into a primitive, applying any field options
+    fn parse<T>(
+        &self,
+        value: &'a str,
+    ) -> std::result::Result<T, String>
+    where
+        T: Primitive,
+        T::Err: Display,
+    {
+        let value = self.normalize(value, T::KIND);
+        if let Some(unit) = self.options.to_nanos {
+            if !matches!(T::KIND, Kind::Signed | Kind::Unsigned) {
+                return Err("to_nanos requires an integer field".into());
+            }
+            let nanos = unit.to_nanos(&value)?;
+            return T::from_i128(nanos)
+                .ok_or_else(|| format!("{} nanoseconds is out of range for the field", nanos));
+        }
+        if self.options.utc_offset {
+            if T::KIND != Kind::Signed {
+                return Err("utc_offset requires a signed integer field".into());
+            }
+            let seconds = parse_utc_offset(&value)?;
+            return T::from_i128(seconds.into())
+                .ok_or_else(|| format!("{} seconds is out of range for the field", seconds));
+        }
+        if let Some(radix) = self.options.radix {
+            return T::from_str_radix(&value, radix)
+                .unwrap_or_else(|| Err("radix requires an integer field".into()));
+        }
+        if let Some(percent) = self.options.percent {
+            if T::KIND != Kind::Float {
+                return Err("percent requires a float field".into());
+            }
+            match value.strip_suffix('%') {
+                Some(number) => {
+                    let number: f64 = number.parse().map_err(|e: ParseFloatError| e.to_string())?;
+                    return T::from_f64(number / 100.0)
+                        .ok_or_else(|| "percent requires a float field".to_string());
+                }
+                None if percent == Percent::Required => {
+                    return Err("expected a percentage ending in %".into())
+                }
+                None => (),
+            }
+        }
+        value.parse().map_err(|e: T::Err| e.to_string())
+    }
+
+    /// Applies field options to a raw value before it's parsed as a primitive of the given kind
+    fn normalize(
+        &self,
+        value: &'a str,
+        kind: Kind,
+    ) -> Cow<'a, str> {
+        let value = match kind {
+            Kind::Bool => value,
+            _ => self
+                .options
+                .strip_units
+                .iter()
+                .filter_map(|unit| value.strip_suffix(unit.as_str()))
+                .min_by_key(|stripped| stripped.len())
+                .unwrap_or(value),
+        };
+        if self.options.lenient_float && kind == Kind::Float {
+            let (sign, magnitude) = match value.strip_prefix('-') {
+                Some(magnitude) => ("-", magnitude),
+                None => ("", value.strip_prefix('+').unwrap_or(value)),
+            };
+            let special = match magnitude.to_ascii_uppercase().as_str() {
+                "∞" | "1.#INF" => Some("inf"),
+                "1.#QNAN" | "1.#SNAN" | "1.#IND" => Some("NaN"),
+                _ => None,
+            };
+            if let Some(special) = special {
+                return Cow::Owned(format!("{}{}", sign, special));
+            }
+        }
+        if self.options.accounting && matches!(kind, Kind::Signed | Kind::Float) {
+            if let Some(inner) = value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+                return Cow::Owned(format!("-{}", inner));
+            }
+        }
+        Cow::Borrowed(value)
+    }
+}
+
+macro_rules! forward_parsed_values {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+                where V: de::Visitor<'de>
+            {
+                let value = match self.value {
+                    Some(value) => value,
+                    None => return <$ty>::default().into_deserializer().$method(visitor),
+                };
+                match self.parse::<$ty>(value) {
+                    Ok(val) => val.into_deserializer().$method(visitor),
+                    Err(e) => Err(de::Error::custom(format_args!("{} while parsing value '{}' provided by {}", e, value, self.name)))
+                }
+            }
+        )*
+    }
 }
 
-/// Deserialize a type from named regex capture groups
-///
-/// See module level documentation for examples
-pub fn from_captures<'a, D>(
-    re: &'a Regex,
-    input: &'a str,
-) -> Result<D>
-where
-    D: Deserialize<'a>,
-{
-    let caps = re.captures(input).ok_or_else(|| {
-        envy::Error::Custom(format!("No captures resolved in string '{}'", input))
-    })?;
-    from_iter(
-        re.capture_names()
-            .map(|maybe_name| {
-                maybe_name.and_then(|name| caps.name(name).map(|val| (name, val.as_str())))
+impl<'a: 'de, 'de, 'o: 'de> de::Deserializer<'de> for Val<'a, 'o> {
+    type Error = Error;
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(nested) = self.nested() {
+            return nested?.deserialize_any(visitor);
+        }
+        if let (true, Some(value)) = (self.options.collapse_whitespace, self.value) {
+            if let Cow::Owned(collapsed) = collapse_whitespace(value) {
+                return visitor.visit_string(collapsed);
+            }
+        }
+        BorrowedStrDeserializer::new(self.value.unwrap_or_default()).deserialize_any(visitor)
+    }
+
+    fn deserialize_str<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        #[cfg(feature = "uuid")]
+        if let (Some(format), Some(value)) = (self.options.uuid_format, self.value) {
+            format.check(value).map_err(|e| {
+                de::Error::custom(format_args!(
+                    "{} while parsing value '{}' provided by {}",
+                    e, value, self.name
+                ))
+            })?;
+        }
+        #[cfg(feature = "chrono")]
+        if let Some(value) = self.value {
+            let formatted = self.options.reformat_chrono(value).map_err(|e| {
+                de::Error::custom(format_args!(
+                    "{} while parsing value '{}' provided by {}",
+                    e, value, self.name
+                ))
+            })?;
+            if let Some(formatted) = formatted {
+                return visitor.visit_string(formatted);
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_map<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.value.is_none() {
+            return visitor.visit_map(MapDeserializer::<_, Error>::new(std::iter::empty::<(
+                &str,
+                &str,
+            )>()));
+        }
+        match self.nested() {
+            Some(nested) => nested?.deserialize_map(visitor),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_char<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(_) => self.deserialize_any(visitor),
+            None => visitor.visit_char(char::default()),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        #[cfg(feature = "iso8601")]
+        if let (true, Some(value)) = (self.options.iso8601_duration, self.value) {
+            let duration = parse_iso8601_duration(value).map_err(|e| {
+                de::Error::custom(format_args!(
+                    "{} while parsing value '{}' provided by {}",
+                    e, value, self.name
+                ))
+            })?;
+            // durations deserialize from their whole seconds and nanoseconds
+            return visitor.visit_seq(SeqDeserializer::<_, Error>::new(
+                [duration.as_secs(), u64::from(duration.subsec_nanos())].into_iter(),
+            ));
+        }
+        if let (Some(bounds), None) = (self.range_bounds(name, fields), &self.options.regex) {
+            let (start, end) = bounds?;
+            return Deserializer::new(vec![("start", start), ("end", end)], &NO_OPTIONS)
+                .deserialize_struct(name, fields, visitor);
+        }
+        match self.nested() {
+            Some(nested) => nested?.deserialize_struct(name, fields, visitor),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (name, options) = (self.name, self.options);
+        if let (Some(re), Some(value), false) =
+            (&options.regex, self.value, options.splits_explicitly())
+        {
+            // each match of the field's regex holds one element
+            let elements = re
+                .captures_iter(value)
+                .map(|caps| matched_deserializer(re, value, &caps, &NO_OPTIONS));
+            return SeqDeserializer::new(elements).deserialize_seq(visitor);
+        }
+        let values = self.elements()?.into_iter().map(|value| Val {
+            name,
+            value: Some(value),
+            options,
+        });
+        SeqDeserializer::new(values).deserialize_seq(visitor)
+    }
+
+    /// Tuples are split into elements as sequences are, and must hold exactly
+    /// as many elements as the tuple's arity
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (name, options) = (self.name, self.options);
+        let elements = self.elements()?;
+        if elements.len() != len {
+            return Err(de::Error::custom(format_args!(
+                "expected {} elements but found {} in value '{}' provided by {}",
+                len,
+                elements.len(),
+                self.value.unwrap_or_default(),
+                name
+            )));
+        }
+        let values = elements.into_iter().map(|value| Val {
+            name,
+            value: Some(value),
+            options,
+        });
+        SeqDeserializer::new(values).deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(value) if self.options.none_values.iter().any(|none| none == value) => {
+                visitor.visit_none()
+            }
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    forward_parsed_values! {
+        bool => deserialize_bool,
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        u128 => deserialize_u128,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        i128 => deserialize_i128,
+        f32 => deserialize_f32,
+        f64 => deserialize_f64,
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let (Some(value), Some((ok, err)), "Result") =
+            (self.value, &self.options.result_prefixes, name)
+        {
+            let (variant, rest) = match (
+                value.strip_prefix(ok.as_str()),
+                value.strip_prefix(err.as_str()),
+            ) {
+                (Some(rest), _) => ("Ok", rest),
+                (None, Some(rest)) => ("Err", rest),
+                (None, None) => {
+                    return Err(de::Error::custom(format_args!(
+                        "expected value '{}' provided by {} to start with '{}' or '{}'",
+                        value, self.name, ok, err
+                    )))
+                }
+            };
+            return visitor.visit_enum(Variant {
+                variant,
+                val: Val {
+                    value: Some(rest),
+                    ..self
+                },
+            });
+        }
+        match (self.value, self.options.enum_by_discriminant) {
+            (Some(value), Some(discriminant)) => {
+                let variant = self.variant_by_discriminant(value, variants, discriminant)?;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            (Some(value), None) => visitor.visit_enum(value.into_deserializer()),
+            (None, _) => Err(de::Error::custom(format_args!(
+                "missing value for field {}",
+                self.name
+            ))),
+        }
+    }
+
+    fn deserialize_bytes<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => visitor.visit_borrowed_bytes(value.as_bytes()),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// Missing values resolve to unit, so that marker fields such
+    /// as a `PhantomData` need no capture of their own
+    fn deserialize_unit<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Some(_) => self.deserialize_any(visitor),
+            None => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        string
+        tuple_struct
+        identifier ignored_any
+    }
+}
+
+/// Deserializes a capture name as an integer map key. Names may carry a leading `_`,
+/// as regex capture names can not start with a digit
+macro_rules! forward_integer_keys {
+    ($($ty:ident => $method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+                where V: de::Visitor<'de>
+            {
+                let name = self.0.strip_prefix('_').unwrap_or(self.0);
+                match name.parse::<$ty>() {
+                    Ok(key) => key.into_deserializer().$method(visitor),
+                    Err(e) => Err(de::Error::custom(format_args!("{} while parsing capture name '{}' as an integer key", e, self.0))),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for VarName<'_> {
+    type Error = Error;
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.0.into_deserializer().deserialize_any(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_integer_keys! {
+        u8 => deserialize_u8,
+        u16 => deserialize_u16,
+        u32 => deserialize_u32,
+        u64 => deserialize_u64,
+        u128 => deserialize_u128,
+        i8 => deserialize_i8,
+        i16 => deserialize_i16,
+        i32 => deserialize_i32,
+        i64 => deserialize_i64,
+        i128 => deserialize_i128,
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string unit seq option
+        bytes byte_buf map unit_struct tuple_struct
+        identifier tuple ignored_any enum
+        struct bool f32 f64
+    }
+}
+
+/// A newtype variant of an enum, selected from a captured value,
+/// whose content is deserialized from the rest of that value
+struct Variant<'a, 'o> {
+    variant: &'static str,
+    val: Val<'a, 'o>,
+}
+
+impl<'a: 'de, 'de, 'o: 'de> de::EnumAccess<'de> for Variant<'a, 'o> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<S>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self)>
+    where
+        S: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a: 'de, 'de, 'o: 'de> de::VariantAccess<'de> for Variant<'a, 'o> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<S>(
+        self,
+        seed: S,
+    ) -> Result<S::Value>
+    where
+        S: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.val)
+    }
+
+    fn tuple_variant<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_tuple(self.val, len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(self.val, self.variant, fields, visitor)
+    }
+}
+
+/// Every value captured for a name across the matches of a regex. Sequences
+/// hold one element per value while anything else is parsed from the last value
+struct Aggregated<'a, 'o> {
+    name: &'o str,
+    values: Vec<&'a str>,
+}
+
+impl<'a, 'o> Aggregated<'a, 'o> {
+    fn last(&self) -> Val<'a, 'o> {
+        Val {
+            name: self.name,
+            value: self.values.last().copied(),
+            options: &NO_FIELD_OPTIONS,
+        }
+    }
+}
+
+impl<'a: 'de, 'de, 'o: 'de> IntoDeserializer<'de, Error> for Aggregated<'a, 'o> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_last_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+                where V: de::Visitor<'de>
+            {
+                self.last().$method(visitor)
+            }
+        )*
+    }
+}
+
+impl<'a: 'de, 'de, 'o: 'de> de::Deserializer<'de> for Aggregated<'a, 'o> {
+    type Error = Error;
+
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let name = self.name;
+        let values = self.values.into_iter().map(|value| Val {
+            name,
+            value: Some(value),
+            options: &NO_FIELD_OPTIONS,
+        });
+        SeqDeserializer::new(values).deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.last().deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_last_value! {
+        deserialize_any deserialize_bool deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_option deserialize_unit
+        deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+}
+
+/// The value of a map entry. Captures matched against bytes which are not
+/// valid UTF-8 may only be deserialized as bytes, borrowing from the input
+enum Entry<'a, 'o> {
+    Val(Val<'a, 'o>),
+    Bytes {
+        name: &'o str,
+        bytes: &'a [u8],
+    },
+    /// The number of capture groups which participated in a match
+    Count(usize),
+}
+
+impl<'a: 'de, 'de, 'o: 'de> IntoDeserializer<'de, Error> for Entry<'a, 'o> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Fails to deserialize bytes which are not valid UTF-8 as anything but bytes
+fn invalid_utf8(
+    name: &str,
+    bytes: &[u8],
+) -> Error {
+    match std::str::from_utf8(bytes) {
+        Err(e) => Error::Custom(format!("{} in value provided by {}", e, name)),
+        Ok(_) => Error::Custom(format!("invalid value provided by {}", name)),
+    }
+}
+
+macro_rules! forward_to_val {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+                where V: de::Visitor<'de>
+            {
+                match self {
+                    Entry::Val(val) => val.$method(visitor),
+                    Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+                    Entry::Count(count) => count.into_deserializer().$method(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'a: 'de, 'de, 'o: 'de> de::Deserializer<'de> for Entry<'a, 'o> {
+    type Error = Error;
+
+    fn deserialize_bytes<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_bytes(visitor),
+            Entry::Bytes { bytes, .. } => visitor.visit_borrowed_bytes(bytes),
+            Entry::Count(count) => count.into_deserializer().deserialize_bytes(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_any(visitor),
+            Entry::Bytes { bytes, .. } => visitor.visit_borrowed_bytes(bytes),
+            Entry::Count(count) => count.into_deserializer().deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_ignored_any(visitor),
+            Entry::Bytes { .. } => visitor.visit_unit(),
+            Entry::Count(count) => count.into_deserializer().deserialize_ignored_any(visitor),
+        }
+    }
+
+    /// Byte sequences such as a `Vec<u8>` hold each of the captured bytes
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_seq(visitor),
+            Entry::Bytes { bytes, .. } => {
+                SeqDeserializer::<_, Error>::new(bytes.iter().copied()).deserialize_seq(visitor)
+            }
+            Entry::Count(count) => count.into_deserializer().deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_option(visitor),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_newtype_struct(name, visitor),
+            other => visitor.visit_newtype_struct(other),
+        }
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_tuple(len, visitor),
+            Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+            Entry::Count(count) => count.into_deserializer().deserialize_tuple(len, visitor),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_unit_struct(name, visitor),
+            Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+            Entry::Count(count) => count
+                .into_deserializer()
+                .deserialize_unit_struct(name, visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_tuple_struct(name, len, visitor),
+            Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+            Entry::Count(count) => count
+                .into_deserializer()
+                .deserialize_tuple_struct(name, len, visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_struct(name, fields, visitor),
+            Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+            Entry::Count(count) => count
+                .into_deserializer()
+                .deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Entry::Val(val) => val.deserialize_enum(name, variants, visitor),
+            Entry::Bytes { name, bytes } => Err(invalid_utf8(name, bytes)),
+            Entry::Count(count) => count
+                .into_deserializer()
+                .deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_val! {
+        deserialize_bool deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_unit deserialize_map deserialize_identifier
+    }
+}
+
+/// A deserializer for named captures
+struct Deserializer<'a, 'o> {
+    vars: Vec<(&'o str, &'a str)>,
+    /// Captures matched against bytes which are not valid UTF-8
+    raw: Vec<(&'o str, &'a [u8])>,
+    options: &'o Options,
+    /// Set to the source pattern when it declares no named capture groups
+    unnamed_pattern: Option<&'o str>,
+    /// The field populated with the number of groups which participated in the match
+    group_count: Option<(&'o str, usize)>,
+}
+
+impl<'a, 'o> Deserializer<'a, 'o> {
+    fn new(
+        vars: Vec<(&'o str, &'a str)>,
+        options: &'o Options,
+    ) -> Self {
+        Deserializer {
+            vars,
+            raw: Vec::new(),
+            options,
+            unnamed_pattern: None,
+            group_count: None,
+        }
+    }
+
+    /// Counts the groups which participated in a match, when the options name a field for it
+    fn with_group_count(
+        mut self,
+        caps: &regex::Captures,
+    ) -> Self {
+        self.group_count = self
+            .options
+            .group_count
+            .as_deref()
+            .map(|name| (name, caps.iter().skip(1).flatten().count()));
+        self
+    }
+
+    /// Binds the input following a match's captures, when the options name a field for it
+    fn with_rest(
+        mut self,
+        input: &'a str,
+        caps: &regex::Captures,
+    ) -> Self {
+        if let Some(name) = self.options.rest.as_deref() {
+            self.vars.push((name, &input[rest_offset(caps)..]));
+        }
+        self
+    }
+
+    fn visit_entries<'de, V>(
+        self,
+        entries: Vec<(&'o str, Option<&'a str>)>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        'a: 'de,
+        'o: 'de,
+        V: de::Visitor<'de>,
+    {
+        let options = self.options;
+        let vals = entries
+            .into_iter()
+            .map(|(name, value)| {
+                let options = options.field(name);
+                Ok((
+                    VarName(name),
+                    Entry::Val(Val {
+                        name,
+                        value: value
+                            .map(|value| options.prepare(name, value))
+                            .transpose()?,
+                        options,
+                    }),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let raw = self
+            .raw
+            .into_iter()
+            .map(|(name, bytes)| (VarName(name), Entry::Bytes { name, bytes }));
+        let count = self
+            .group_count
+            .map(|(name, count)| (VarName(name), Entry::Count(count)));
+        visitor.visit_map(MapDeserializer::new(
+            vals.into_iter().chain(raw).chain(count),
+        ))
+    }
+}
+
+impl<'a: 'de, 'de, 'o: 'de> de::Deserializer<'de> for Deserializer<'a, 'o> {
+    type Error = Error;
+    fn deserialize_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let entries = self.vars.iter().map(|&(k, v)| (k, Some(v))).collect();
+        self.visit_entries(entries, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let (Some(pattern), false) = (self.unnamed_pattern, fields.is_empty()) {
+            return Err(de::Error::custom(format_args!(
+                "pattern declares no named capture groups. Use (?P<name>...) or (?<name>...) to capture fields in '{}'",
+                pattern
+            )));
+        }
+        if let Some(rule) = self.options.rename_all {
+            let renamed: Vec<_> = fields.iter().map(|field| rule.apply(field)).collect();
+            let field_of = |name: &str| {
+                renamed
+                    .iter()
+                    .position(|renamed| renamed == name)
+                    .map(|position| fields[position])
+            };
+            for (name, _) in &mut self.vars {
+                *name = field_of(name).unwrap_or(name);
+            }
+            for (name, _) in &mut self.raw {
+                *name = field_of(name).unwrap_or(name);
+            }
+        }
+        for &field in fields {
+            let group = match &self.options.field(field).variant_by {
+                Some(group) => group,
+                None => continue,
+            };
+            let position = self
+                .vars
+                .iter()
+                .position(|&(k, _)| k == group)
+                .ok_or_else(|| {
+                    de::Error::custom(format_args!(
+                        "missing capture {} selecting the variant of field {}",
+                        group, field
+                    ))
+                })?;
+            let (_, value) = self.vars.remove(position);
+            self.vars.push((field, value));
+        }
+        for &field in fields {
+            let group = match &self.options.field(field).flag_group {
+                Some(group) => group,
+                None => continue,
+            };
+            let present = match self.vars.iter().position(|&(k, _)| k == group) {
+                Some(position) => {
+                    self.vars.remove(position);
+                    true
+                }
+                None => false,
+            };
+            self.vars
+                .push((field, if present { "true" } else { "false" }));
+        }
+        for &field in fields {
+            let raw = match &self.options.field(field).keep_raw {
+                Some(raw) => raw,
+                None => continue,
+            };
+            if let Some(&(_, value)) = self.vars.iter().find(|&&(k, _)| k == field) {
+                self.vars.push((raw, value));
+            }
+        }
+        for &field in fields {
+            let options = self.options.field(field);
+            let group = match &options.counted_by {
+                Some(group) => group,
+                None => continue,
+            };
+            let count = self
+                .vars
+                .iter()
+                .find(|&&(k, _)| k == group)
+                .ok_or_else(|| {
+                    de::Error::custom(format_args!(
+                        "missing capture {} counting the elements of field {}",
+                        group, field
+                    ))
+                })?
+                .1;
+            let expected: usize = count.trim().parse().map_err(|e| {
+                de::Error::custom(format_args!(
+                    "{} while parsing count '{}' provided by {}",
+                    e, count, group
+                ))
+            })?;
+            let found = match self.vars.iter().find(|&&(k, _)| k == field) {
+                Some(&(_, value)) => Val {
+                    name: field,
+                    value: Some(options.prepare(field, value)?),
+                    options,
+                }
+                .elements()?
+                .len(),
+                None => 0,
+            };
+            if expected != found {
+                return Err(de::Error::custom(format_args!(
+                    "expected {} elements of field {}, as counted by {}, but found {}",
+                    expected, field, group, found
+                )));
+            }
+        }
+        let mut entries: Vec<_> = self.vars.iter().map(|&(k, v)| (k, Some(v))).collect();
+        for &field in fields {
+            if self.vars.iter().any(|&(k, _)| k == field)
+                || self.raw.iter().any(|&(k, _)| k == field)
+                || self.group_count.is_some_and(|(k, _)| k == field)
+            {
+                continue;
+            }
+            match self.options.on_missing(field) {
+                OnMissing::Error => return Err(de::Error::missing_field(field)),
+                OnMissing::Default => entries.push((field, None)),
+                OnMissing::None => (),
+            }
+        }
+        self.visit_entries(entries, visitor)
+    }
+
+    /// Sequences are deserialized from the value of a pattern's single named capture
+    fn deserialize_seq<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.vars.as_slice() {
+            &[(name, value)] => {
+                let options = self.options.field(name);
+                Val {
+                    name,
+                    value: Some(options.prepare(name, value)?),
+                    options,
+                }
+                .deserialize_seq(visitor)
+            }
+            _ => Err(de::Error::custom(
+                "sequences require a pattern with a single named capture group matching the list",
+            )),
+        }
+    }
+
+    /// Newtypes are deserialized from the value of a pattern's single named capture
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.vars.as_slice() {
+            &[(name, value)] => {
+                let options = self.options.field(name);
+                visitor.visit_newtype_struct(Val {
+                    name,
+                    value: Some(options.prepare(name, value)?),
+                    options,
+                })
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Tuples are deserialized from the captures of [`Options::field_order`] in order.
+    /// A group which did not participate in a match resolves to a missing value, which
+    /// is `None` for `Option` fields, unless the [`OnMissing::Error`] policy applies
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let options = self.options;
+        if options.field_order.len() != len {
+            return Err(de::Error::custom(format_args!(
+                "tuples require a field_order naming each of their {} elements",
+                len
+            )));
+        }
+        let mut values = Vec::with_capacity(len);
+        for name in &options.field_order {
+            let name = name.as_str();
+            let value = self.vars.iter().find(|&&(k, _)| k == name).map(|&(_, v)| v);
+            if value.is_none() && options.on_missing(name) == OnMissing::Error {
+                return Err(de::Error::custom(format_args!("missing field `{}`", name)));
+            }
+            let field = options.field(name);
+            values.push(Val {
+                name,
+                value: value.map(|value| field.prepare(name, value)).transpose()?,
+                options: field,
+            });
+        }
+        SeqDeserializer::new(values.into_iter()).deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit
+        bytes byte_buf unit_struct
+        identifier ignored_any option enum
+    }
+}
+
+/// Deserialize a type from an iterable of `(&str, &str)` pairs
+/// representing field names and their values
+///
+/// This is the generic entry point to recap's deserializer, independent of
+/// regex. Values are parsed exactly as captured values would be, so it may be
+/// used to deserialize key value pairs from any other source
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Example {
+///   foo: String,
+///   bar: u32,
+/// }
+///
+/// # fn main() -> Result<(), recap::Error> {
+/// let example: Example = recap::from_pairs(vec![("foo", "hello"), ("bar", "42")])?;
+/// assert_eq!(example, Example { foo: "hello".into(), bar: 42 });
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_pairs<'a, D, I>(pairs: I) -> Result<D>
+where
+    D: Deserialize<'a>,
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    D::deserialize(Deserializer::new(pairs.into_iter().collect(), &NO_OPTIONS))
+}
+
+/// Deserialize a type from a [logfmt](https://brandur.org/logfmt) line of
+/// whitespace separated `key=value` pairs, such as `level=info msg="hi there" count=3`
+///
+/// Pairs may appear in any order. Values may be quoted to include whitespace, and
+/// quoted values may escape `"` and `\` with a `\`, as well as `\n`, `\r` and `\t`.
+/// Keys written without a value resolve to an empty value. Values are parsed
+/// exactly as captured values would be, see [`from_pairs`]. Because quoted values
+/// may be unescaped into new strings, the target type can not borrow fields from
+/// the input
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Entry {
+///   level: String,
+///   msg: String,
+///   count: u32,
+/// }
+///
+/// # fn main() -> Result<(), recap::Error> {
+/// let entry: Entry = recap::from_logfmt(r#"count=3 level=info msg="hi \"there\"""#)?;
+/// assert_eq!(
+///   entry,
+///   Entry { level: "info".into(), msg: r#"hi "there""#.into(), count: 3 }
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_logfmt<D>(input: &str) -> Result<D>
+where
+    D: DeserializeOwned,
+{
+    let pairs = logfmt_pairs(input)?;
+    from_pairs(pairs.iter().map(|(key, value)| (*key, value.as_ref())))
+}
+
+/// Splits a logfmt line into its keys and unescaped values
+fn logfmt_pairs(input: &str) -> Result<Vec<(&str, Cow<'_, str>)>> {
+    let mut pairs = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        if key.is_empty() {
+            return Err(Error::Custom(format!(
+                "missing logfmt key before '{}'",
+                rest
+            )));
+        }
+        rest = &rest[key_end..];
+        let value = match rest.strip_prefix('=') {
+            Some(quoted) if quoted.starts_with('"') => {
+                let (value, remaining) = logfmt_quoted(&quoted[1..]).ok_or_else(|| {
+                    Error::Custom(format!("unterminated quoted logfmt value for key {}", key))
+                })?;
+                rest = remaining;
+                value
+            }
+            Some(value) => {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                rest = &value[end..];
+                Cow::Borrowed(&value[..end])
+            }
+            None => Cow::Borrowed(""),
+        };
+        pairs.push((key, value));
+        rest = rest.trim_start();
+    }
+    Ok(pairs)
+}
+
+/// Resolves the unescaped value of a quoted logfmt value following its opening
+/// quote, along with the input remaining after its closing quote
+fn logfmt_quoted(input: &str) -> Option<(Cow<'_, str>, &str)> {
+    let end = input.find(['"', '\\'])?;
+    if input[end..].starts_with('"') {
+        return Some((Cow::Borrowed(&input[..end]), &input[end + 1..]));
+    }
+    let mut value = String::from(&input[..end]);
+    let mut chars = input[end..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => return Some((Cow::Owned(value), &input[end + offset + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                escaped @ ('"' | '\\') => value.push(escaped),
+                other => {
+                    value.push('\\');
+                    value.push(other);
+                }
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Deserialize a type from named regex capture groups
+///
+/// Maps, such as a `HashMap<String, String>`, hold an entry for each capture
+/// which participated in the match, keyed by its name. With the `json` feature this
+/// includes a `serde_json::Map<String, serde_json::Value>`, whose values are each a
+/// `Value::String` of the captured text, as captures are not typed without a struct
+///
+/// Maps keyed by integers, such as a `BTreeMap<u8, String>`, require capture names
+/// which parse as integers once an optional leading `_` is removed, like `_1`, as
+/// regex capture names can not start with a digit
+///
+/// See module level documentation for examples
+pub fn from_captures<'a, D>(
+    re: &'a Regex,
+    input: &'a str,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    from_captures_with_options(re, input, &NO_OPTIONS)
+}
+
+/// Deserialize a type from the named capture groups of a pattern compiled at runtime
+///
+/// Both an invalid pattern and a failure to deserialize its captures are reported
+/// as a recap [`Error`], so dynamic patterns need only handle a single error type.
+/// Prefer [`from_captures`] with a reused [`Regex`] when applying the same pattern
+/// to many inputs, as this compiles the pattern on each call. Because the pattern
+/// does not outlive the call, the target type can not borrow fields from the input
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Example {
+///   foo: String,
+/// }
+///
+/// # fn main() -> Result<(), recap::Error> {
+/// let example: Example = recap::from_pattern(r#"(?P<foo>\S+)"#, "hello")?;
+/// assert_eq!(example, Example { foo: "hello".into() });
+/// assert!(recap::from_pattern::<Example>(r#"(?P<foo>"#, "hello").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_pattern<D>(
+    pattern: &str,
+    input: &str,
+) -> Result<D>
+where
+    D: DeserializeOwned,
+{
+    let re =
+        Regex::new(pattern).map_err(|e| Error::Custom(format!("invalid regex pattern: {}", e)))?;
+    from_captures(&re, input)
+}
+
+/// Deserialize an owned type from named regex capture groups of any
+/// string-like input, such as a `String`, `&String` or `Cow<str>`
+///
+/// Because the input may be owned or temporary, the target type can not
+/// borrow fields from it. Use [`from_captures`] for zero-copy deserialization
+pub fn from_captures_ref<D, S>(
+    re: &Regex,
+    input: S,
+) -> Result<D>
+where
+    D: DeserializeOwned,
+    S: AsRef<str>,
+{
+    from_captures(re, input.as_ref())
+}
+
+/// Deserialize a type from named regex capture groups, prefixing any error
+/// with some context such as the file and line the input was read from
+///
+/// ```rust
+/// use recap::Regex;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Entry {
+///   level: String,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"^(?P<level>[A-Z]+)$"#)?;
+/// let err = recap::from_captures_with_context::<Entry, _>(&re, "oops", "app.log:42")
+///     .unwrap_err();
+/// assert!(err.to_string().starts_with("app.log:42: "));
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_captures_with_context<'a, D, C>(
+    re: &'a Regex,
+    input: &'a str,
+    context: C,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+    C: Display,
+{
+    from_captures(re, input).map_err(|e| Error::Custom(format!("{}: {}", context, e)))
+}
+
+/// Deserialize a type from named regex capture groups, applying
+/// the provided [`Options`]
+///
+/// This is what `#[derive(Recap)]` generated code calls with options
+/// resolved from `#[recap(...)]` attributes. The options are borrowed for as long
+/// as the input, as borrowed fields may hold values remapped by [`FieldOptions::map`]
+pub fn from_captures_with_options<'a, D>(
+    re: &'a Regex,
+    input: &'a str,
+    options: &'a Options,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    D::deserialize(captures_deserializer(re, input, options)?)
+}
+
+/// Deserialize a type from every match of a regex within some input, collecting
+/// all of the values captured for each name
+///
+/// A [`Regex::captures`] match only keeps the last value of a group which is
+/// repeated, or of a name which is otherwise matched many times. Here each match
+/// found by [`Regex::captures_iter`] contributes its values instead, so a sequence
+/// field holds every value captured for its name, in order, with one element per
+/// value. Other fields are parsed from the last value captured for their name.
+/// This costs a scan of the whole input and the resolution of every match's captures,
+/// rather than of a single match, so prefer [`from_captures`] when a name is only
+/// captured once
+///
+/// ```rust
+/// use recap::Regex;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Post {
+///   author: String,
+///   tags: Vec<String>,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"by (?P<author>\w+)|#(?P<tags>\w+)"#)?;
+/// let post: Post = recap::from_captures_aggregated(&re, "#rust by ferris #serde")?;
+/// assert_eq!(post.author, "ferris");
+/// assert_eq!(post.tags, vec!["rust", "serde"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_captures_aggregated<'a, D>(
+    re: &'a Regex,
+    input: &'a str,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    let mut entries: Vec<(VarName, Aggregated)> = re
+        .capture_names()
+        .flatten()
+        .map(|name| {
+            (
+                VarName(name),
+                Aggregated {
+                    name,
+                    values: Vec::new(),
+                },
+            )
+        })
+        .collect();
+    for caps in re.captures_iter(input) {
+        for (VarName(name), aggregated) in &mut entries {
+            if let Some(value) = caps.name(name) {
+                aggregated.values.push(value.as_str());
+            }
+        }
+    }
+    entries.retain(|(_, aggregated)| !aggregated.values.is_empty());
+    D::deserialize(MapDeserializer::new(entries.into_iter()))
+}
+
+/// Deserialize a type from each match of a regex within some input, along with
+/// the byte range of the overall match
+///
+/// Matches are visited left to right and do not overlap, as with
+/// [`Regex::captures_iter`]. A match which fails to deserialize does not stop
+/// the remaining matches from being visited
+///
+/// ```rust
+/// use recap::Regex;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Pair {
+///   key: String,
+///   value: u32,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"(?P<key>\w+)=(?P<value>\w+)"#)?;
+/// let found = recap::find_all_with_spans::<Pair>(&re, "a=1 b=x");
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(found[0].1, 0..3);
+/// assert!(found[1].0.is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_all_with_spans<'a, D>(
+    re: &'a Regex,
+    input: &'a str,
+) -> Vec<(Result<D>, Range<usize>)>
+where
+    D: Deserialize<'a>,
+{
+    re.captures_iter(input)
+        .map(|caps| {
+            let span = caps.get(0).expect("whole match").range();
+            let result = D::deserialize(matched_deserializer(re, input, &caps, &NO_OPTIONS));
+            (result, span)
+        })
+        .collect()
+}
+
+/// Deserialize a type from each of many lines, separating successes from failures
+///
+/// Failures are paired with the zero based index of the line which produced them.
+/// Lines which do not match the regex count as failures unless `skip_unmatched` is
+/// set, in which case they are ignored
+///
+/// ```rust
+/// use recap::Regex;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Count {
+///   n: u32,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"n=(?P<n>\S+)"#)?;
+/// let lines = vec!["n=1", "n=x", "# comment", "n=3"];
+/// let (counts, failures) = recap::parse_many::<Count, _>(&re, lines.clone(), false);
+/// assert_eq!(counts, vec![Count { n: 1 }, Count { n: 3 }]);
+/// assert_eq!(failures.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![1, 2]);
+///
+/// let (_, failures) = recap::parse_many::<Count, _>(&re, lines, true);
+/// assert_eq!(failures.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_many<'a, D, I>(
+    re: &'a Regex,
+    lines: I,
+    skip_unmatched: bool,
+) -> (Vec<D>, Vec<(usize, Error)>)
+where
+    D: Deserialize<'a>,
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut parsed = Vec::new();
+    let mut failures = Vec::new();
+    for (index, line) in lines.into_iter().enumerate() {
+        if skip_unmatched && !re.is_match(line) {
+            continue;
+        }
+        match from_captures(re, line) {
+            Ok(value) => parsed.push(value),
+            Err(err) => failures.push((index, err)),
+        }
+    }
+    (parsed, failures)
+}
+
+/// The reason a single line failed in [`from_captures_iter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemErrorKind {
+    /// The regex did not match the line
+    NoMatch,
+    /// The regex matched but its captures failed to deserialize
+    Deserialize,
+    /// The regex matched more lines than allowed by
+    /// [`CapturesIter::max_matches_or_error`]. This is the last item yielded
+    LimitExceeded,
+}
+
+/// A failure to deserialize a single line yielded by [`from_captures_iter`]
+#[derive(Debug)]
+pub struct ItemError {
+    index: usize,
+    line: String,
+    kind: ItemErrorKind,
+    error: Error,
+}
+
+impl ItemError {
+    /// The zero based index of the line which failed
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The line which failed
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Whether the line failed to match or to deserialize
+    pub fn kind(&self) -> ItemErrorKind {
+        self.kind
+    }
+
+    /// The underlying error
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Consumes this error, returning the underlying error
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl Display for ItemError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.index, self.error)
+    }
+}
+
+impl std::error::Error for ItemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Lazily deserialize a type from each of many lines, yielding an item per line
+///
+/// Unlike [`parse_many`], failures are reported in line order alongside successes
+/// as an [`ItemError`] whose [`kind`](ItemError::kind) tells lines which did not
+/// match apart from lines which matched but failed to deserialize. Streaming
+/// consumers may filter on the kind, for instance to skip unmatched lines while
+/// stopping at the first line which failed to deserialize
+///
+/// ```rust
+/// use recap::{ItemErrorKind, Regex};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Count {
+///   n: u32,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"n=(?P<n>\S+)"#)?;
+/// let lines = vec!["n=1", "# comment", "n=3"];
+/// let counts = recap::from_captures_iter::<Count, _>(&re, lines)
+///     .filter(|item| !matches!(item, Err(e) if e.kind() == ItemErrorKind::NoMatch))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(counts, vec![Count { n: 1 }, Count { n: 3 }]);
+///
+/// let failed = recap::from_captures_iter::<Count, _>(&re, vec!["n=x"])
+///     .find_map(Result::err)
+///     .expect("a failure");
+/// assert_eq!(failed.kind(), ItemErrorKind::Deserialize);
+/// assert_eq!(failed.line(), "n=x");
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_captures_iter<'a, D, I>(
+    re: &'a Regex,
+    lines: I,
+) -> CapturesIter<'a, D, I::IntoIter>
+where
+    D: Deserialize<'a>,
+    I: IntoIterator<Item = &'a str>,
+{
+    CapturesIter {
+        re,
+        lines: lines.into_iter().enumerate(),
+        limit: None,
+        matches: 0,
+        finished: false,
+        deserialized: std::marker::PhantomData,
+    }
+}
+
+/// An iterator deserializing a type from each of many lines, created by
+/// [`from_captures_iter`]
+///
+/// The number of lines the regex may match can be bounded, to limit the resources
+/// spent on untrusted input. Once the limit is reached, the iterator either ends
+/// silently with [`max_matches`](CapturesIter::max_matches) or yields a final
+/// [`ItemErrorKind::LimitExceeded`] error with
+/// [`max_matches_or_error`](CapturesIter::max_matches_or_error). Lines which do not
+/// match do not count towards the limit. No further lines are read once the
+/// limit has been enforced
+///
+/// ```rust
+/// use recap::{ItemErrorKind, Regex};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Count {
+///   n: u32,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = Regex::new(r#"n=(?P<n>\d+)"#)?;
+/// let lines = vec!["n=1", "n=2", "n=3"];
+/// let counts = recap::from_captures_iter::<Count, _>(&re, lines.clone()).max_matches(2);
+/// assert_eq!(counts.count(), 2);
+///
+/// let last = recap::from_captures_iter::<Count, _>(&re, lines)
+///     .max_matches_or_error(2)
+///     .last()
+///     .expect("an item");
+/// assert_eq!(last.unwrap_err().kind(), ItemErrorKind::LimitExceeded);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CapturesIter<'a, D, I> {
+    re: &'a Regex,
+    lines: std::iter::Enumerate<I>,
+    /// The maximum number of matches and whether exceeding it is an error
+    limit: Option<(usize, bool)>,
+    matches: usize,
+    finished: bool,
+    deserialized: std::marker::PhantomData<fn() -> D>,
+}
+
+impl<D, I> CapturesIter<'_, D, I> {
+    /// Ends iteration, without an error, once the regex has matched `max` lines
+    pub fn max_matches(
+        mut self,
+        max: usize,
+    ) -> Self {
+        self.limit = Some((max, false));
+        self
+    }
+
+    /// Ends iteration with an [`ItemErrorKind::LimitExceeded`] error when the
+    /// regex matches more than `max` lines
+    pub fn max_matches_or_error(
+        mut self,
+        max: usize,
+    ) -> Self {
+        self.limit = Some((max, true));
+        self
+    }
+}
+
+impl<'a, D, I> Iterator for CapturesIter<'a, D, I>
+where
+    D: Deserialize<'a>,
+    I: Iterator<Item = &'a str>,
+{
+    type Item = std::result::Result<D, ItemError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if let Some((max, false)) = self.limit {
+            if self.matches == max {
+                self.finished = true;
+                return None;
+            }
+        }
+        let (index, line) = self.lines.next()?;
+        let (kind, error) = match captures_deserializer(self.re, line, &NO_OPTIONS) {
+            Ok(_) if matches!(self.limit, Some((max, true)) if self.matches == max) => {
+                self.finished = true;
+                let error = Error::Custom(format!(
+                    "matched more than the maximum of {} lines",
+                    self.matches
+                ));
+                (ItemErrorKind::LimitExceeded, error)
+            }
+            Ok(deserializer) => {
+                self.matches += 1;
+                match D::deserialize(deserializer) {
+                    Ok(value) => return Some(Ok(value)),
+                    Err(error) => (ItemErrorKind::Deserialize, error),
+                }
+            }
+            Err(error) => (ItemErrorKind::NoMatch, error),
+        };
+        Some(Err(ItemError {
+            index,
+            line: line.to_string(),
+            kind,
+            error,
+        }))
+    }
+}
+
+/// Incrementally deserializes records from a stream of text arriving in chunks,
+/// such as reads from a socket, where a record may be split across chunks
+///
+/// Records are separated by a terminator, `\n` by default, and parsed as soon as
+/// their terminator arrives. Only the trailing incomplete record is buffered between
+/// calls to [`feed`](StreamParser::feed), so buffering is bounded by the length of
+/// the longest record plus the size of a chunk. A stream which never sends a
+/// terminator is buffered in full until it is [`flush`](StreamParser::flush)ed.
+/// Empty records, as between consecutive terminators, are skipped
+///
+/// ```rust
+/// use recap::{Regex, StreamParser};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Reading {
+///   sensor: String,
+///   value: f64,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut parser = StreamParser::<Reading>::new(Regex::new(r#"^(?P<sensor>\w+)=(?P<value>\S+)$"#)?);
+/// assert!(parser.feed("temp=21.").is_empty());
+/// let readings = parser.feed("5\nhumidity=0.4");
+/// assert_eq!(readings.len(), 1);
+/// assert_eq!(readings[0].as_ref().unwrap().value, 21.5);
+/// assert_eq!(parser.flush().transpose()?.unwrap().sensor, "humidity");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamParser<D> {
+    re: Regex,
+    terminator: String,
+    buffer: String,
+    /// Offset into the buffer up to which no terminator starts
+    scanned: usize,
+    parsed: std::marker::PhantomData<fn() -> D>,
+}
+
+impl<D> StreamParser<D>
+where
+    D: DeserializeOwned,
+{
+    /// Creates a parser applying a regex to records terminated by `\n`
+    pub fn new(re: Regex) -> Self {
+        Self::with_terminator(re, "\n")
+    }
+
+    /// Creates a parser applying a regex to records separated by a terminator
+    ///
+    /// # Panics
+    ///
+    /// Panics when the terminator is empty
+    pub fn with_terminator(
+        re: Regex,
+        terminator: impl Into<String>,
+    ) -> Self {
+        let terminator = terminator.into();
+        assert!(
+            !terminator.is_empty(),
+            "record terminator must not be empty"
+        );
+        StreamParser {
+            re,
+            terminator,
+            buffer: String::new(),
+            scanned: 0,
+            parsed: std::marker::PhantomData,
+        }
+    }
+
+    /// Buffers a chunk of the stream, returning each record it completed in order
+    pub fn feed(
+        &mut self,
+        chunk: &str,
+    ) -> Vec<Result<D>> {
+        self.buffer.push_str(chunk);
+        let mut records = Vec::new();
+        let mut start = 0;
+        while let Some(end) = self.buffer[self.scanned..]
+            .find(&self.terminator)
+            .map(|offset| self.scanned + offset)
+        {
+            let record = &self.buffer[start..end];
+            if !record.is_empty() {
+                records.push(from_captures(&self.re, record));
+            }
+            start = end + self.terminator.len();
+            self.scanned = start;
+        }
+        self.buffer.drain(..start);
+        // a terminator split across chunks may start within the last few bytes
+        let mut scanned = self.buffer.len().saturating_sub(self.terminator.len() - 1);
+        while !self.buffer.is_char_boundary(scanned) {
+            scanned -= 1;
+        }
+        self.scanned = scanned;
+        records
+    }
+
+    /// Parses any buffered record which has not been terminated, as when a stream
+    /// ends without a trailing terminator. Returns `None` when nothing is buffered
+    pub fn flush(&mut self) -> Option<Result<D>> {
+        self.scanned = 0;
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let record = std::mem::take(&mut self.buffer);
+        Some(from_captures(&self.re, &record))
+    }
+}
+
+/// Deserialize a type from the union of named capture groups of several
+/// regexes, each applied to the same input
+///
+/// This allows fields to be contributed by separate patterns, such as a common
+/// prefix pattern and a payload pattern. Every pattern must match the input and
+/// capture names may not be declared by more than one pattern
+pub fn from_multi_captures<'a, D>(
+    res: &[&'a Regex],
+    input: &'a str,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    let mut vars: Vec<(&'a str, &'a str)> = Vec::new();
+    let mut declared: Vec<&'a str> = Vec::new();
+    for re in res {
+        let caps = re.captures(input).ok_or_else(|| {
+            envy::Error::Custom(format!(
+                "No captures resolved in string '{}' for pattern '{}'",
+                input,
+                re.as_str()
+            ))
+        })?;
+        for name in re.capture_names().flatten() {
+            if declared.contains(&name) {
+                return Err(envy::Error::Custom(format!(
+                    "capture group '{}' is declared by more than one pattern",
+                    name
+                )));
+            }
+            declared.push(name);
+            if let Some(val) = caps.name(name) {
+                vars.push((name, val.as_str()));
+            }
+        }
+    }
+    D::deserialize(Deserializer::new(vars, &NO_OPTIONS))
+}
+
+/// Deserialize a type from named regex capture groups matched against bytes
+///
+/// Captured values must be valid UTF-8 unless they are deserialized as bytes.
+/// Fields such as `&'a [u8]`, or a `Cow<'a, [u8]>` marked `#[serde(borrow)]`,
+/// borrow their captured bytes from the input without copying them, so they
+/// may not outlive the input buffer. A `Vec<u8>` field holds a copy of them
+///
+/// ```rust
+/// use recap::BytesRegex;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Frame<'a> {
+///   kind: &'a str,
+///   payload: &'a [u8],
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let re = BytesRegex::new(r#"(?s-u)(?P<kind>\w+):(?P<payload>.*)"#)?;
+/// let frame: Frame = recap::from_captures_bytes(&re, b"data:\xff\x00")?;
+/// assert_eq!(frame, Frame { kind: "data", payload: b"\xff\x00" });
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_captures_bytes<'a, D>(
+    re: &'a BytesRegex,
+    input: &'a [u8],
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    from_captures_bytes_with_options(re, input, &NO_OPTIONS)
+}
+
+/// Deserialize a type from named regex capture groups matched against bytes,
+/// applying the provided [`Options`]
+///
+/// Captured values must be valid UTF-8 unless they are deserialized as bytes,
+/// see [`from_captures_bytes`]. As with [`from_captures_with_options`], the options
+/// are borrowed for as long as the input
+pub fn from_captures_bytes_with_options<'a, D>(
+    re: &'a BytesRegex,
+    input: &'a [u8],
+    options: &'a Options,
+) -> Result<D>
+where
+    D: Deserialize<'a>,
+{
+    let caps = re.captures(input).ok_or_else(|| {
+        envy::Error::Custom(format!(
+            "No captures resolved in bytes '{}'",
+            String::from_utf8_lossy(input)
+        ))
+    })?;
+    let mut deserializer = Deserializer::new(Vec::new(), options);
+    deserializer.group_count = options
+        .group_count
+        .as_deref()
+        .map(|name| (name, caps.iter().skip(1).flatten().count()));
+    if let Some(name) = options.rest.as_deref() {
+        let rest = &input[rest_bytes_offset(&caps)..];
+        match std::str::from_utf8(rest) {
+            Ok(value) if !options.field(name).raw_bytes => deserializer.vars.push((name, value)),
+            _ => deserializer.raw.push((name, rest)),
+        }
+    }
+    let mut push = |name, val: regex::bytes::Match<'a>| match std::str::from_utf8(val.as_bytes()) {
+        Ok(value) if !options.field(name).raw_bytes => deserializer.vars.push((name, value)),
+        _ => deserializer.raw.push((name, val.as_bytes())),
+    };
+    if !options.field_order.is_empty() {
+        for (index, name) in options.field_order.iter().enumerate() {
+            if let Some(val) = caps.get(index + 1) {
+                push(name.as_str(), val);
+            }
+        }
+        return D::deserialize(deserializer);
+    }
+    for name in re.capture_names().flatten() {
+        if let Some(val) = caps.name(name) {
+            push(name, val);
+        }
+    }
+    if re.capture_names().flatten().next().is_none() {
+        deserializer.unnamed_pattern = Some(re.as_str());
+    }
+    D::deserialize(deserializer)
+}
+
+/// Decodes bytes in the encoding with the given WHATWG label, such as
+/// `windows-1252` or `utf-16le`. Malformed input fails rather than being replaced.
+/// Used by derived `try_from_encoded` methods
+#[cfg(feature = "encoding")]
+#[doc(hidden)]
+pub fn decode<'b>(
+    bytes: &'b [u8],
+    label: &str,
+) -> Result<Cow<'b, str>> {
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| Error::Custom(format!("unknown encoding '{}'", label)))?;
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or_else(|| Error::Custom(format!("input is not valid {}", encoding.name())))
+}
+
+/// Fails with the position of the first character of an input matched by a pattern
+/// of disallowed characters. Used by `#[recap(charset = "...")]` derived code
+#[doc(hidden)]
+pub fn check_charset(
+    disallowed: &Regex,
+    input: &str,
+) -> Result<()> {
+    match disallowed.find(input) {
+        Some(found) => Err(Error::Custom(format!(
+            "unexpected character {:?} at byte {} of input '{}'",
+            found.as_str(),
+            found.start(),
+            input
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Replaces each run of whitespace in an input with a single space. Inputs without
+/// such runs are borrowed. Used by `#[recap(collapse_whitespace)]` derived code
+#[doc(hidden)]
+pub fn collapse_whitespace(input: &str) -> Cow<'_, str> {
+    let mut chars = input.chars().peekable();
+    let mut needed = false;
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() && (c != ' ' || chars.peek().is_some_and(|c| c.is_whitespace())) {
+            needed = true;
+            break;
+        }
+    }
+    if !needed {
+        return Cow::Borrowed(input);
+    }
+    let mut collapsed = String::with_capacity(input.len());
+    let mut in_whitespace = false;
+    for c in input.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+        } else if !in_whitespace {
+            collapsed.push(' ');
+        }
+        in_whitespace = c.is_whitespace();
+    }
+    Cow::Owned(collapsed)
+}
+
+/// Replaces the sub patterns at the given sorted spans of a pattern with
+/// escaped literal values. Spans without a value, or nested within an already
+/// replaced span, are left as is. Used by derived `to_search_regex` methods
+#[doc(hidden)]
+pub fn splice_pattern(
+    pattern: &str,
+    groups: &[(Range<usize>, Option<String>)],
+) -> String {
+    let mut spliced = String::with_capacity(pattern.len());
+    let mut last = 0;
+    for (span, value) in groups {
+        let value = match value {
+            Some(value) if span.start >= last => value,
+            _ => continue,
+        };
+        spliced.push_str(&pattern[last..span.start]);
+        // disable verbose mode so whitespace in values is matched literally
+        spliced.push_str("(?-x:");
+        spliced.push_str(&regex::escape(value));
+        spliced.push(')');
+        last = span.end;
+    }
+    spliced.push_str(&pattern[last..]);
+    spliced
+}
+
+/// Parses a named capture with its type's `FromStr` impl, recording any failure
+/// in `errors`. Used by `#[recap(from_str)]` derived code so that all failing
+/// fields may be reported together
+#[doc(hidden)]
+pub fn parse_capture<T>(
+    caps: &regex::Captures,
+    name: &str,
+    required: bool,
+    errors: &mut Vec<String>,
+) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = match caps.name(name) {
+        Some(value) => value.as_str(),
+        None => {
+            if required {
+                errors.push(format!("missing value for field {}", name));
+            }
+            return None;
+        }
+    };
+    value
+        .parse()
+        .map_err(|e| {
+            errors.push(format!(
+                "{} while parsing value '{}' provided by {}",
+                e, value, name
+            ))
+        })
+        .ok()
+}
+
+/// Resolves a deserializer over the named captures of a regex applied to some input
+fn captures_deserializer<'a, 'o>(
+    re: &'o Regex,
+    input: &'a str,
+    options: &'o Options,
+) -> Result<Deserializer<'a, 'o>> {
+    let caps = re.captures(input).ok_or_else(|| {
+        envy::Error::Custom(format!("No captures resolved in string '{}'", input))
+    })?;
+    Ok(matched_deserializer(re, input, &caps, options))
+}
+
+/// Resolves a deserializer over the named captures of a single match
+fn matched_deserializer<'a, 'o>(
+    re: &'o Regex,
+    input: &'a str,
+    caps: &regex::Captures<'a>,
+    options: &'o Options,
+) -> Deserializer<'a, 'o> {
+    if !options.field_order.is_empty() {
+        let vars = options
+            .field_order
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                caps.get(index + 1).map(|val| (name.as_str(), val.as_str()))
+            })
+            .collect();
+        return Deserializer::new(vars, options)
+            .with_group_count(caps)
+            .with_rest(input, caps);
+    }
+    let mut deserializer = Deserializer::new(
+        re.capture_names()
+            .filter_map(|maybe_name| {
+                maybe_name.and_then(|name| caps.name(name).map(|val| (name, val.as_str())))
+            })
+            .collect(),
+        options,
+    );
+    if re.capture_names().flatten().next().is_none() {
+        deserializer.unnamed_pattern = Some(re.as_str());
+    }
+    deserializer.with_group_count(caps).with_rest(input, caps)
+}
+
+/// Resolves the offset following the last participating capture group of a
+/// match, or following the whole match when no group participated
+fn rest_offset(caps: &regex::Captures) -> usize {
+    let whole = caps.get(0).map_or(0, |m| m.end());
+    caps.iter()
+        .skip(1)
+        .flatten()
+        .map(|m| m.end())
+        .max()
+        .unwrap_or(whole)
+}
+
+/// Resolves the offset following the last participating capture group of a
+/// match against bytes, or following the whole match when no group participated
+fn rest_bytes_offset(caps: &regex::bytes::Captures) -> usize {
+    let whole = caps.get(0).map_or(0, |m| m.end());
+    caps.iter()
+        .skip(1)
+        .flatten()
+        .map(|m| m.end())
+        .max()
+        .unwrap_or(whole)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_captures, from_captures_aggregated, from_captures_bytes,
+        from_captures_bytes_with_options, from_captures_iter, from_captures_with_context,
+        from_captures_with_options, from_logfmt, from_pattern, BytesRegex, FieldOptions,
+        ItemErrorKind, OnMissing, Options, Regex, RenameRule, StreamParser,
+    };
+    use serde::Deserialize;
+    use std::{borrow::Cow, error::Error};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct LogEntry {
+        foo: String,
+        bar: String,
+        baz: String,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct LogEntryOptional {
+        foo: String,
+        bar: String,
+        baz: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct LogEntryBorrowed<'a> {
+        foo: &'a str,
+        bar: &'a str,
+        baz: &'a str,
+    }
+
+    #[test]
+    fn deserializes_matching_captures_optional() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            from_captures::<LogEntryOptional>(
+                &Regex::new(
+                    r#"(?x)
+                    (?P<foo>\S+)
+                    \s+
+                    (?P<bar>\S+)
+                    \s+
+                    (?P<baz>\S+)?
+                "#
+                )?,
+                "one two "
+            )?,
+            LogEntryOptional {
+                foo: "one".into(),
+                bar: "two".into(),
+                baz: None
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_matching_captures() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            from_captures::<LogEntry>(
+                &Regex::new(
+                    r#"(?x)
+                    (?P<foo>\S+)
+                    \s+
+                    (?P<bar>\S+)
+                    \s+
+                    (?P<baz>\S+)
+                "#
+                )?,
+                "one two three"
+            )?,
+            LogEntry {
+                foo: "one".into(),
+                bar: "two".into(),
+                baz: "three".into()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_zero_copy() -> Result<(), Box<dyn Error>> {
+        let input = "one two three";
+        assert_eq!(
+            from_captures::<LogEntryBorrowed>(
+                &Regex::new(
+                    r#"(?x)
+                    (?P<foo>\S+)
+                    \s+
+                    (?P<bar>\S+)
+                    \s+
+                    (?P<baz>\S+)
+                "#
+                )?,
+                input
+            )?,
+            LogEntryBorrowed {
+                foo: "one",
+                bar: "two",
+                baz: "three"
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fails_without_captures() -> Result<(), Box<dyn Error>> {
+        let result = from_captures::<LogEntry>(&Regex::new("test")?, "one two three");
+        match result {
+            Ok(_) => panic!("should have failed"),
+            // enum variants on type aliases are experimental
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "No captures resolved in string \'one two three\'"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn fails_with_unmatched_captures() -> Result<(), Box<dyn Error>> {
+        let result = from_captures::<LogEntry>(&Regex::new(r#"(?P<bar>\S+)"#)?, "one two three");
+        match result {
+            Ok(_) => panic!("should have failed"),
+            // enum variants on type aliases are experimental
+            Err(err) => assert_eq!(err.to_string(), "missing value for field foo"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn defaults_missing_captures_with_on_missing_default() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Sparse {
+            foo: String,
+            bar: u32,
+            baz: Vec<String>,
+            qux: Option<bool>,
+        }
+
+        let options = Options {
+            on_missing: OnMissing::Default,
+            ..Options::default()
+        };
+        assert_eq!(
+            from_captures_with_options::<Sparse>(
+                &Regex::new(r#"(?P<foo>\S+)(\s(?P<bar>\d+))?"#)?,
+                "one",
+                &options
+            )?,
+            Sparse {
+                foo: "one".into(),
+                bar: 0,
+                baz: vec![],
+                qux: None
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn field_on_missing_takes_precedence() -> Result<(), Box<dyn Error>> {
+        let mut options = Options {
+            on_missing: OnMissing::Default,
+            ..Options::default()
+        };
+        options.fields.insert(
+            "baz".into(),
+            FieldOptions {
+                on_missing: Some(OnMissing::Error),
+                ..FieldOptions::default()
+            },
+        );
+        let result = from_captures_with_options::<LogEntryOptional>(
+            &Regex::new(r#"(?P<foo>\S+)\s(?P<bar>\S+)"#)?,
+            "one two",
+            &options,
+        );
+        match result {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(err.to_string(), "missing value for field baz"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_accounting_negatives() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Ledger {
+            debit: i32,
+            credit: f64,
+        }
+
+        let mut options = Options::default();
+        for field in ["debit", "credit"] {
+            options.fields.insert(
+                field.into(),
+                FieldOptions {
+                    accounting: true,
+                    ..FieldOptions::default()
+                },
+            );
+        }
+        let re = Regex::new(r#"(?P<debit>\S+)\s(?P<credit>\S+)"#)?;
+        assert_eq!(
+            from_captures_with_options::<Ledger>(&re, "(42) 42.5", &options)?,
+            Ledger {
+                debit: -42,
+                credit: 42.5
+            }
+        );
+        assert_eq!(
+            from_captures_with_options::<Ledger>(&re, "42 (42.5)", &options)?,
+            Ledger {
+                debit: 42,
+                credit: -42.5
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fails_without_named_captures() -> Result<(), Box<dyn Error>> {
+        let result = from_captures::<LogEntry>(&Regex::new(r#"(\S+) (\S+)"#)?, "one two");
+        match result {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "pattern declares no named capture groups. Use (?P<name>...) or (?<name>...) to capture fields in '(\\S+) (\\S+)'"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_matching_byte_captures() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            from_captures_bytes::<LogEntryBorrowed>(
+                &BytesRegex::new(r#"(?P<foo>\S+)\s(?P<bar>\S+)\s(?P<baz>\S+)"#)?,
+                b"one two three"
+            )?,
+            LogEntryBorrowed {
+                foo: "one",
+                bar: "two",
+                baz: "three"
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fails_with_invalid_utf8_byte_captures() -> Result<(), Box<dyn Error>> {
+        let result = from_captures_bytes::<LogEntry>(
+            &BytesRegex::new(r#"(?-u)(?P<foo>\S+)\s(?P<bar>\S+)\s(?P<baz>\S+)"#)?,
+            b"one \xff three",
+        );
+        match result {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "invalid utf-8 sequence of 1 bytes from index 0 in value provided by bar"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_invalid_utf8_byte_captures_as_bytes() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Record<'a> {
+            #[serde(borrow)]
+            key: Cow<'a, [u8]>,
+            value: Vec<u8>,
+            checksum: Option<&'a [u8]>,
+        }
+        let re = BytesRegex::new(r#"(?-u)(?P<key>\S+)=(?P<value>\S+)(?: (?P<checksum>\S+))?"#)?;
+        let record = from_captures_bytes::<Record>(&re, b"\xfe=\xff\x01 ok")?;
+        assert!(matches!(record.key, Cow::Borrowed(b"\xfe")));
+        assert_eq!(record.value, vec![0xff, 0x01]);
+        assert_eq!(record.checksum, Some(&b"ok"[..]));
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_raw_bytes_fields_from_valid_utf8_captures() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Record {
+            value: Vec<u8>,
+        }
+        let re = BytesRegex::new(r#"(?s-u)v=(?P<value>.*)"#)?;
+        let mut options = Options::default();
+        options.fields.insert(
+            "value".into(),
+            FieldOptions {
+                raw_bytes: true,
+                ..FieldOptions::default()
+            },
+        );
+        for input in [&b"v=ab"[..], b"v=\x01\x02", b"v=\xff\x01"] {
+            let record: Record = from_captures_bytes_with_options(&re, input, &options)?;
+            assert_eq!(record.value, &input[2..]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn splits_sequences_with_delimiter_regex() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Tagged {
+            tags: Vec<String>,
+        }
+
+        let mut options = Options::default();
+        options.fields.insert(
+            "tags".into(),
+            FieldOptions {
+                delimiter_regex: Some(Regex::new(r#"\s*;\s*"#)?),
+                ..FieldOptions::default()
+            },
+        );
+        assert_eq!(
+            from_captures_with_options::<Tagged>(
+                &Regex::new(r#"tags=(?P<tags>.*)"#)?,
+                "tags=a ; b;;c",
+                &options
+            )?,
+            Tagged {
+                tags: vec!["a".into(), "b".into(), "".into(), "c".into()]
+            }
+        );
+        // a trailing delimiter is followed by an empty element
+        assert_eq!(
+            from_captures_with_options::<Tagged>(
+                &Regex::new(r#"tags=(?P<tags>.*)"#)?,
+                "tags=a;b;",
+                &options
+            )?,
+            Tagged {
+                tags: vec!["a".into(), "b".into(), "".into()]
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_pattern_reports_invalid_patterns() {
+        #[derive(Debug, Deserialize)]
+        struct Example {
+            #[allow(dead_code)]
+            foo: String,
+        }
+
+        match from_pattern::<Example>("(?P<foo>", "hello") {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert!(err.to_string().starts_with("invalid regex pattern: ")),
+        }
+    }
+
+    #[test]
+    fn binds_numbered_groups_in_field_order() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Pair {
+            a: String,
+            b: u32,
+        }
+
+        let options = Options {
+            field_order: vec!["b".into(), "a".into()],
+            ..Options::default()
+        };
+        assert_eq!(
+            from_captures_with_options::<Pair>(&Regex::new(r#"(\d+)=(\w+)"#)?, "42=x", &options)?,
+            Pair {
+                a: "x".into(),
+                b: 42
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_sequences_from_a_single_capture() -> Result<(), Box<dyn Error>> {
+        let re = Regex::new(r#"ids=(?P<ids>\S+)"#)?;
+        assert_eq!(from_captures::<Vec<u32>>(&re, "ids=1,2,3")?, vec![1, 2, 3]);
+        match from_captures::<Vec<u32>>(&Regex::new(r#"(?P<a>\d+) (?P<b>\d+)"#)?, "1 2") {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "sequences require a pattern with a single named capture group matching the list"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_ranges_from_delimited_values() -> Result<(), Box<dyn Error>> {
+        use std::ops::{Range, RangeInclusive};
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Span {
+            lines: Range<u32>,
+            offsets: RangeInclusive<i32>,
+        }
+
+        let mut options = Options::default();
+        options.fields.insert(
+            "offsets".into(),
+            FieldOptions {
+                range_delimiter: Some("..".into()),
+                ..FieldOptions::default()
+            },
+        );
+        let re = Regex::new(r#"(?P<lines>\S+) (?P<offsets>\S+)"#)?;
+        assert_eq!(
+            from_captures_with_options::<Span>(&re, "10-20 -5..-3", &options)?,
+            Span {
+                lines: 10..20,
+                offsets: -5..=-3
+            }
+        );
+        match from_captures_with_options::<Span>(&re, "20-10 1..2", &options) {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "range start 20 is greater than its end 10 in value provided by lines"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_maps_with_integer_keys() -> Result<(), Box<dyn Error>> {
+        use std::collections::BTreeMap;
+
+        let columns: BTreeMap<u8, String> = from_captures(
+            &Regex::new(r#"(?P<_1>\S+) (?P<_2>\S+) (?P<_3>\S+)"#)?,
+            "a b c",
+        )?;
+        assert_eq!(
+            columns.into_iter().collect::<Vec<_>>(),
+            vec![(1, "a".into()), (2, "b".into()), (3, "c".into())]
+        );
+        let columns: BTreeMap<u8, String> = super::from_pairs(vec![("1", "a"), ("2", "b")])?;
+        assert_eq!(columns.len(), 2);
+        match from_captures::<BTreeMap<u8, String>>(&Regex::new(r#"(?P<one>\S+)"#)?, "a") {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "invalid digit found in string while parsing capture name 'one' as an integer key"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn iterates_captures_reporting_failures_by_kind() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Count {
+            n: u32,
+        }
+        let re = Regex::new(r#"^n=(?P<n>\S+)$"#)?;
+        let items = from_captures_iter::<Count, _>(&re, vec!["n=1", "nope", "n=x", "n=4"])
+            .map(|item| item.map_err(|e| (e.index(), e.line().to_string(), e.kind())))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                Ok(Count { n: 1 }),
+                Err((1, "nope".into(), ItemErrorKind::NoMatch)),
+                Err((2, "n=x".into(), ItemErrorKind::Deserialize)),
+                Ok(Count { n: 4 }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn enforces_max_matches() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Count {
+            n: u32,
+        }
+        let re = Regex::new(r#"^n=(?P<n>\S+)$"#)?;
+        let lines = ["n=1", "skip", "n=x", "n=3", "n=4"];
+        let read = std::cell::Cell::new(0);
+        let counted = || {
+            lines.iter().map(|line| {
+                read.set(read.get() + 1);
+                *line
             })
-            .flatten(),
-    )
-}
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::{from_captures, Regex};
-    use serde::Deserialize;
-    use std::error::Error;
+        let items: Vec<_> = from_captures_iter::<Count, _>(&re, counted())
+            .max_matches(2)
+            .collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(read.get(), 3);
 
-    #[derive(Debug, PartialEq, Deserialize)]
-    struct LogEntry {
-        foo: String,
-        bar: String,
-        baz: String,
+        read.set(0);
+        let kinds: Vec<_> = from_captures_iter::<Count, _>(&re, counted())
+            .max_matches_or_error(2)
+            .map(|item| item.map_err(|e| e.kind()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Ok(Count { n: 1 }),
+                Err(ItemErrorKind::NoMatch),
+                Err(ItemErrorKind::Deserialize),
+                Err(ItemErrorKind::LimitExceeded),
+            ]
+        );
+        assert_eq!(read.get(), 4);
+        Ok(())
     }
 
-    #[derive(Debug, PartialEq, Deserialize)]
-    struct LogEntryOptional {
-        foo: String,
-        bar: String,
-        baz: Option<String>,
+    #[test]
+    fn streams_records_split_across_chunks() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Count {
+            n: u32,
+        }
+        let mut parser =
+            StreamParser::<Count>::with_terminator(Regex::new(r#"^n=(?P<n>\d+)$"#)?, "\r\n");
+        let mut parsed = Vec::new();
+        for chunk in ["n=1\r", "\nn=", "2\r\n\r\nn=x\r\nn=", "3"] {
+            parsed.extend(parser.feed(chunk).into_iter().map(|r| r.ok()));
+        }
+        assert_eq!(
+            parsed,
+            vec![Some(Count { n: 1 }), Some(Count { n: 2 }), None]
+        );
+        assert_eq!(parser.flush().transpose()?, Some(Count { n: 3 }));
+        assert!(parser.flush().is_none());
+        Ok(())
     }
 
-    #[derive(Debug, PartialEq, Deserialize)]
-    struct LogEntryBorrowed<'a> {
-        foo: &'a str,
-        bar: &'a str,
-        baz: &'a str,
+    #[test]
+    fn aggregates_values_captured_by_many_matches() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Totals {
+            label: Option<String>,
+            counts: Vec<u32>,
+        }
+        let re = Regex::new(r#"(?P<counts>\d+)(?:=(?P<label>\w+))?"#)?;
+        assert_eq!(
+            from_captures_aggregated::<Totals>(&re, "1 2=a 3=b 4")?,
+            Totals {
+                label: Some("b".into()),
+                counts: vec![1, 2, 3, 4],
+            }
+        );
+        assert_eq!(
+            from_captures_aggregated::<Totals>(&re, "5")?,
+            Totals {
+                label: None,
+                counts: vec![5],
+            }
+        );
+        Ok(())
     }
 
     #[test]
-    fn deserializes_matching_captures_optional() -> Result<(), Box<dyn Error>> {
+    fn parses_logfmt_lines() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Router {
+            at: String,
+            method: String,
+            path: String,
+            fwd: String,
+            status: u16,
+            bytes: u64,
+            protocol: Option<String>,
+        }
         assert_eq!(
-            from_captures::<LogEntryOptional>(
-                &Regex::new(
-                    r#"(?x)
-                    (?P<foo>\S+)
-                    \s+
-                    (?P<bar>\S+)
-                    \s+
-                    (?P<baz>\S+)?
-                "#
-                )?,
-                "one two "
+            from_logfmt::<Router>(
+                r#"at=info method=GET path=/ host=myapp.herokuapp.com fwd="1.2.3.4" dyno=web.1 connect=1ms service=18ms status=200 bytes=13"#
             )?,
-            LogEntryOptional {
-                foo: "one".into(),
-                bar: "two".into(),
-                baz: None
+            Router {
+                at: "info".into(),
+                method: "GET".into(),
+                path: "/".into(),
+                fwd: "1.2.3.4".into(),
+                status: 200,
+                bytes: 13,
+                protocol: None,
+            }
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Message {
+            msg: String,
+            err: String,
+            debug: String,
+        }
+        assert_eq!(
+            from_logfmt::<Message>(
+                r#"  err="open \"a b\": no such file\n" debug msg="stopping server"  "#
+            )?,
+            Message {
+                msg: "stopping server".into(),
+                err: "open \"a b\": no such file\n".into(),
+                debug: "".into(),
             }
         );
 
+        match from_logfmt::<Message>(r#"msg="unterminated"#) {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "unterminated quoted logfmt value for key msg"
+            ),
+        }
+        assert!(from_logfmt::<Message>("=oops").is_err());
         Ok(())
     }
 
     #[test]
-    fn deserializes_matching_captures() -> Result<(), Box<dyn Error>> {
+    fn context_prefixes_errors() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct LogEntry {
+            level: String,
+            code: u16,
+        }
+        let re = Regex::new(r#"^(?P<level>[A-Z]+) (?P<code>\S+)$"#).unwrap();
         assert_eq!(
-            from_captures::<LogEntry>(
-                &Regex::new(
-                    r#"(?x)
-                    (?P<foo>\S+)
-                    \s+
-                    (?P<bar>\S+)
-                    \s+
-                    (?P<baz>\S+)
-                "#
-                )?,
-                "one two three"
-            )?,
+            from_captures_with_context::<LogEntry, _>(&re, "WARN 42", "app.log:1").unwrap(),
             LogEntry {
-                foo: "one".into(),
-                bar: "two".into(),
-                baz: "three".into()
+                level: "WARN".into(),
+                code: 42
+            }
+        );
+        let err = from_captures_with_context::<LogEntry, _>(&re, "WARN x", "app.log:42")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("app.log:42: "), "{}", err);
+        assert!(err.ends_with("provided by code"), "{}", err);
+    }
+
+    #[test]
+    fn rename_rules_apply_conventions() {
+        let field = "request_id";
+        assert_eq!(RenameRule::CamelCase.apply(field), "requestId");
+        assert_eq!(RenameRule::PascalCase.apply(field), "RequestId");
+        assert_eq!(RenameRule::ScreamingSnakeCase.apply(field), "REQUEST_ID");
+    }
+
+    #[test]
+    fn optional_tails_cascade_as_the_pattern_does() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Tail {
+            a: String,
+            b: Option<String>,
+            c: Option<String>,
+        }
+        let tail = |a: &str, b: Option<&str>, c: Option<&str>| Tail {
+            a: a.into(),
+            b: b.map(Into::into),
+            c: c.map(Into::into),
+        };
+        // b's group is tried first and matches anything c's would
+        let re = Regex::new(r#"^(?P<a>\w+)(?:\s+(?P<b>\w+))?(?:\s+(?P<c>\w+))?$"#).unwrap();
+        assert_eq!(
+            from_captures::<Tail>(&re, "x").unwrap(),
+            tail("x", None, None)
+        );
+        assert_eq!(
+            from_captures::<Tail>(&re, "x y").unwrap(),
+            tail("x", Some("y"), None)
+        );
+        assert_eq!(
+            from_captures::<Tail>(&re, "x y z").unwrap(),
+            tail("x", Some("y"), Some("z"))
+        );
+
+        // groups matching different values resolve independently
+        let re = Regex::new(r#"^(?P<a>\w+)(?:\s+(?P<b>\d+))?(?:\s+(?P<c>[a-z]+))?$"#).unwrap();
+        assert_eq!(
+            from_captures::<Tail>(&re, "x").unwrap(),
+            tail("x", None, None)
+        );
+        assert_eq!(
+            from_captures::<Tail>(&re, "x 1").unwrap(),
+            tail("x", Some("1"), None)
+        );
+        assert_eq!(
+            from_captures::<Tail>(&re, "x z").unwrap(),
+            tail("x", None, Some("z"))
+        );
+        assert_eq!(
+            from_captures::<Tail>(&re, "x 1 z").unwrap(),
+            tail("x", Some("1"), Some("z"))
+        );
+
+        // nesting c within b's group rejects c without b
+        let re = Regex::new(r#"^(?P<a>\w+)(?:\s+(?P<b>\d+)(?:\s+(?P<c>[a-z]+))?)?$"#).unwrap();
+        assert_eq!(
+            from_captures::<Tail>(&re, "x 1 z").unwrap(),
+            tail("x", Some("1"), Some("z"))
+        );
+        assert!(from_captures::<Tail>(&re, "x z").is_err());
+    }
+
+    #[test]
+    fn deserializes_128_bit_integers() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Masks {
+            mask: u128,
+            offset: i128,
+        }
+        let re = Regex::new(r#"^(?P<mask>\d+) (?P<offset>-?\d+)$"#).unwrap();
+        assert_eq!(
+            from_captures::<Masks>(
+                &re,
+                "340282366920938463463374607431768211455 -18446744073709551617"
+            )
+            .unwrap(),
+            Masks {
+                mask: u128::MAX,
+                offset: -18_446_744_073_709_551_617,
             }
         );
+        match from_captures::<Masks>(&re, "340282366920938463463374607431768211456 0") {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "number too large to fit in target type while parsing value '340282366920938463463374607431768211456' provided by mask"
+            ),
+        }
+    }
+
+    #[test]
+    fn rename_rules_apply_to_runs_of_underscores() {
+        for (rule, renamed) in [
+            (RenameRule::CamelCase, "aBC"),
+            (RenameRule::PascalCase, "ABC"),
+            (RenameRule::ScreamingSnakeCase, "A__B_C"),
+        ] {
+            assert_eq!(rule.apply("a__b_c"), renamed);
+        }
+    }
+
+    #[test]
+    fn collapses_runs_of_whitespace() {
+        use super::collapse_whitespace;
+        use std::borrow::Cow;
+
+        assert!(matches!(
+            collapse_whitespace("a b c"),
+            Cow::Borrowed("a b c")
+        ));
+        assert_eq!(collapse_whitespace("a \t b\tc  "), "a b c ");
+    }
+
+    #[test]
+    fn parses_percentages_as_fractions() -> Result<(), Box<dyn Error>> {
+        use super::Percent;
+
+        #[derive(Debug, Deserialize)]
+        struct Usage {
+            rate: f64,
+        }
+
+        let re = Regex::new(r#"(?P<rate>\S+)"#)?;
+        let parse = |input, percent| {
+            let mut options = Options::default();
+            options.fields.insert(
+                "rate".into(),
+                FieldOptions {
+                    percent: Some(percent),
+                    ..FieldOptions::default()
+                },
+            );
+            from_captures_with_options::<Usage>(&re, input, &options).map(|usage| usage.rate)
+        };
+        assert_eq!(parse("95%", Percent::Optional)?, 0.95);
+        assert_eq!(parse("0.5", Percent::Optional)?, 0.5);
+        assert_eq!(parse("95%", Percent::Required)?, 0.95);
+        match parse("0.5", Percent::Required) {
+            Ok(_) => panic!("should have failed"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "expected a percentage ending in % while parsing value '0.5' provided by rate"
+            ),
+        }
 
         Ok(())
     }
 
     #[test]
-    fn deserializes_zero_copy() -> Result<(), Box<dyn Error>> {
-        let input = "one two three";
+    fn parses_special_floats() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, Deserialize)]
+        struct Reading {
+            value: f64,
+        }
+
+        let mut options = Options::default();
+        options.fields.insert(
+            "value".into(),
+            FieldOptions {
+                lenient_float: true,
+                ..FieldOptions::default()
+            },
+        );
+        let re = Regex::new(r#"(?P<value>\S+)"#)?;
+        let parse = |input| {
+            from_captures_with_options::<Reading>(&re, input, &options).map(|reading| reading.value)
+        };
+        for input in ["inf", "+Inf", "Infinity", "∞", "+∞", "1.#INF", "1.#inf"] {
+            assert_eq!(parse(input)?, f64::INFINITY, "{}", input);
+        }
+        for input in ["-INFINITY", "-∞", "-1.#INF"] {
+            assert_eq!(parse(input)?, f64::NEG_INFINITY, "{}", input);
+        }
+        for input in ["NaN", "nan", "-nan", "1.#QNAN", "1.#SNAN", "-1.#IND"] {
+            // NaN is never equal to itself
+            assert!(parse(input)?.is_nan(), "{}", input);
+        }
+        assert!(parse("1.#INFINITE").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_sets_from_sequences() -> Result<(), Box<dyn Error>> {
+        use std::collections::{BTreeSet, HashSet};
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Tagged {
+            tags: HashSet<String>,
+            ordered: BTreeSet<u32>,
+        }
+
+        let tagged: Tagged = from_captures(
+            &Regex::new(r#"(?P<tags>\S+) (?P<ordered>\S+)"#)?,
+            "b,a,b 3,1,2,1",
+        )?;
+        assert_eq!(tagged.tags, ["a", "b"].iter().map(|&t| t.into()).collect());
         assert_eq!(
-            from_captures::<LogEntryBorrowed>(
-                &Regex::new(
-                    r#"(?x)
-                    (?P<foo>\S+)
-                    \s+
-                    (?P<bar>\S+)
-                    \s+
-                    (?P<baz>\S+)
-                "#
-                )?,
-                input
+            tagged.ordered.into_iter().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn splits_sequences_outside_of_quotes() -> Result<(), Box<dyn Error>> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Tagged {
+            tags: Vec<String>,
+        }
+
+        let mut options = Options::default();
+        options.fields.insert(
+            "tags".into(),
+            FieldOptions {
+                quote_char: Some('"'),
+                ..FieldOptions::default()
+            },
+        );
+        assert_eq!(
+            from_captures_with_options::<Tagged>(
+                &Regex::new(r#"tags=(?P<tags>.*)"#)?,
+                r#"tags="a,b",c"#,
+                &options
             )?,
+            Tagged {
+                tags: vec!["a,b".into(), "c".into()]
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn scales_time_units_to_nanos() {
+        use super::TimeUnit;
+
+        assert_eq!(TimeUnit::Secs.to_nanos("2"), Ok(2_000_000_000));
+        assert_eq!(TimeUnit::Millis.to_nanos("1.5"), Ok(1_500_000));
+        assert_eq!(TimeUnit::Micros.to_nanos("-0.25"), Ok(-250));
+        assert_eq!(TimeUnit::Nanos.to_nanos("7.9"), Ok(7));
+        assert!(TimeUnit::Secs.to_nanos("1s").is_err());
+        assert!(TimeUnit::Secs.to_nanos(".").is_err());
+        // fractions may carry a whole number of seconds past the bounds of an i128
+        assert_eq!(
+            TimeUnit::Secs.to_nanos("170141183460469231731687303715.884105727"),
+            Ok(i128::MAX)
+        );
+        assert_eq!(
+            TimeUnit::Secs.to_nanos("170141183460469231731687303715.999999999"),
+            Err("number too large to represent in nanoseconds".into())
+        );
+    }
+
+    #[test]
+    fn deserializes_pairs() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            super::from_pairs::<LogEntryBorrowed, _>(vec![
+                ("foo", "one"),
+                ("bar", "two"),
+                ("baz", "three")
+            ])?,
             LogEntryBorrowed {
                 foo: "one",
                 bar: "two",
@@ -472,27 +4553,63 @@ mod tests {
     }
 
     #[test]
-    fn fails_without_captures() -> Result<(), Box<dyn Error>> {
-        let result = from_captures::<LogEntry>(&Regex::new("test")?, "one two three");
-        match result {
-            Ok(_) => panic!("should have failed"),
-            // enum variants on type aliases are experimental
-            Err(err) => assert_eq!(
-                err.to_string(),
-                "No captures resolved in string \'one two three\'"
-            ),
-        }
+    fn deserializes_angle_bracket_named_captures() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            from_captures::<LogEntry>(
+                &Regex::new(r#"(?<foo>\S+)\s(?P<bar>\S+)\s(?<baz>\S+)"#)?,
+                "one two three"
+            )?,
+            LogEntry {
+                foo: "one".into(),
+                bar: "two".into(),
+                baz: "three".into()
+            }
+        );
 
         Ok(())
     }
 
     #[test]
-    fn fails_with_unmatched_captures() -> Result<(), Box<dyn Error>> {
-        let result = from_captures::<LogEntry>(&Regex::new(".+")?, "one two three");
-        match result {
+    fn deserializes_string_like_inputs() -> Result<(), Box<dyn Error>> {
+        let re = Regex::new(r#"(?P<foo>\S+)\s(?P<bar>\S+)\s(?P<baz>\S+)"#)?;
+        let input = String::from("one two three");
+        let expected = LogEntry {
+            foo: "one".into(),
+            bar: "two".into(),
+            baz: "three".into(),
+        };
+        assert_eq!(
+            super::from_captures_ref::<LogEntry, _>(&re, &input)?,
+            expected
+        );
+        assert_eq!(
+            super::from_captures_ref::<LogEntry, _>(&re, input)?,
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserializes_union_of_multiple_captures() -> Result<(), Box<dyn Error>> {
+        let prefix = Regex::new(r#"^(?P<foo>\S+)"#)?;
+        let suffix = Regex::new(r#"(?P<bar>\S+)\s(?P<baz>\S+)$"#)?;
+        assert_eq!(
+            super::from_multi_captures::<LogEntry>(&[&prefix, &suffix], "one - two three")?,
+            LogEntry {
+                foo: "one".into(),
+                bar: "two".into(),
+                baz: "three".into()
+            }
+        );
+
+        let conflicting = Regex::new(r#"(?P<foo>\S+)$"#)?;
+        match super::from_multi_captures::<LogEntry>(&[&prefix, &conflicting], "one two") {
             Ok(_) => panic!("should have failed"),
-            // enum variants on type aliases are experimental
-            Err(err) => assert_eq!(err.to_string(), "missing value for field foo"),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "capture group 'foo' is declared by more than one pattern"
+            ),
         }
 
         Ok(())
@@ -0,0 +1,87 @@
+//! Parsing of asynchronous streams of lines
+//!
+//! This module is available with the `futures` feature. It integrates recap with
+//! async readers by mapping each line a `futures::Stream` yields through
+//! [`from_captures`](crate::from_captures).
+
+use crate::{from_captures, Regex, Result};
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Parses each line of a stream with a regex, yielding a result for each
+///
+/// Lines are owned `String`s which are dropped once parsed, so targets must be
+/// [`DeserializeOwned`] and can not borrow from the line they were parsed from.
+///
+/// The returned stream applies backpressure: a line is only pulled from `lines`
+/// when the returned stream is polled for its next item, and no lines are buffered.
+/// Lines which fail to parse, including lines which do not match the regex, are
+/// yielded as errors rather than ending the stream.
+///
+/// ```rust
+/// use futures::{executor::block_on, stream, StreamExt};
+/// use recap::{stream::parse_stream, Regex};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Reading {
+///     sensor: String,
+///     value: f64,
+/// }
+///
+/// let re = Regex::new(r#"^(?P<sensor>\w+)=(?P<value>\S+)$"#).unwrap();
+/// let lines = stream::iter(vec!["a=1.5".to_string(), "b=x".to_string()]);
+/// let results: Vec<Result<Reading, recap::Error>> = block_on(parse_stream(&re, lines).collect());
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
+/// ```
+pub fn parse_stream<'r, D, S>(
+    re: &'r Regex,
+    lines: S,
+) -> impl Stream<Item = Result<D>> + 'r
+where
+    D: DeserializeOwned + 'r,
+    S: Stream<Item = String> + 'r,
+{
+    ParseStream {
+        re,
+        lines: Box::pin(lines),
+        target: PhantomData,
+    }
+}
+
+/// A stream parsing each line of another
+struct ParseStream<'r, D, S> {
+    re: &'r Regex,
+    // pinned on the heap so that streams which are not `Unpin` are accepted
+    lines: Pin<Box<S>>,
+    target: PhantomData<fn() -> D>,
+}
+
+impl<D, S> Stream for ParseStream<'_, D, S>
+where
+    D: DeserializeOwned,
+    S: Stream<Item = String>,
+{
+    type Item = Result<D>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.lines
+            .as_mut()
+            .poll_next(cx)
+            .map(|line| line.map(|line| from_captures(this.re, &line)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+}
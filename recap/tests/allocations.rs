@@ -0,0 +1,120 @@
+//! Counts allocations made while parsing to show that per-field options
+//! are looked up, and borrowed byte fields are parsed, without allocating
+//!
+//! Allocations are counted per thread, so each test only observes its own
+
+use recap::{
+    from_captures_bytes, from_captures_with_options, BytesRegex, FieldOptions, Options, Regex,
+};
+use serde::Deserialize;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    error::Error,
+};
+
+struct Counting;
+
+thread_local! {
+    // counted per thread, as the test harness runs tests on parallel threads
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The number of allocations made so far by the current thread
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(
+        &self,
+        layout: Layout,
+    ) -> *mut u8 {
+        // the count is unavailable once a thread's locals are destroyed
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+    ) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Entry<'a> {
+    host: &'a str,
+    port: u16,
+    path: &'a str,
+}
+
+/// Counts the allocations made by a single parse, after warming up
+fn allocations_per_parse(
+    re: &Regex,
+    options: &Options,
+) -> Result<usize, Box<dyn Error>> {
+    let input = "localhost 8080 /index.html";
+    from_captures_with_options::<Entry>(re, input, options)?;
+    let before = allocations();
+    from_captures_with_options::<Entry>(re, input, options)?;
+    Ok(allocations() - before)
+}
+
+#[test]
+fn field_options_lookups_do_not_allocate() -> Result<(), Box<dyn Error>> {
+    let re = Regex::new(r#"(?P<host>\S+) (?P<port>\d+) (?P<path>\S+)"#)?;
+    let mut options = Options::default();
+    let without = allocations_per_parse(&re, &options)?;
+    for field in ["host", "port", "path"] {
+        options.fields.insert(
+            field.into(),
+            FieldOptions {
+                trim: true,
+                ..FieldOptions::default()
+            },
+        );
+    }
+    assert_eq!(allocations_per_parse(&re, &options)?, without);
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Packet<'a> {
+    kind: &'a str,
+    payload: &'a [u8],
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Kind<'a> {
+    kind: &'a str,
+}
+
+#[test]
+fn borrowed_bytes_fields_do_not_allocate() -> Result<(), Box<dyn Error>> {
+    let re = BytesRegex::new(r#"(?s-u)(?P<kind>\w+):(?P<payload>.*)"#)?;
+    let input: &[u8] = b"data:\xff\xfe\x00\x01";
+    let packet: Packet = from_captures_bytes(&re, input)?;
+    assert_eq!(packet.payload, b"\xff\xfe\x00\x01");
+    assert!(input.as_ptr_range().contains(&packet.payload.as_ptr()));
+
+    // both types are parsed once before counting, as allocations_per_parse warms up
+    from_captures_bytes::<Kind>(&re, input)?;
+    let before = allocations();
+    from_captures_bytes::<Kind>(&re, input)?;
+    let without_payload = allocations() - before;
+    let before = allocations();
+    from_captures_bytes::<Packet>(&re, input)?;
+    assert_eq!(allocations() - before, without_payload);
+
+    Ok(())
+}
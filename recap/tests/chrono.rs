@@ -0,0 +1,68 @@
+#![cfg(all(feature = "derive", feature = "chrono"))]
+
+use chrono::{NaiveDate, NaiveTime};
+use recap::Recap;
+use serde::Deserialize;
+use std::error::Error;
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<date>\S+) (?P<time>\S+ [AP]M)"#)]
+struct Entry {
+    #[recap(date_format = "%d/%m/%Y")]
+    date: NaiveDate,
+    #[recap(time_format = "%I:%M %p")]
+    time: NaiveTime,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<date>\S+)(?: (?P<time>\S+))?"#)]
+struct Stamp {
+    #[recap(date_format = "%Y%m%d")]
+    date: NaiveDate,
+    #[recap(time_format = "%H:%M:%S")]
+    time: Option<NaiveTime>,
+}
+
+#[test]
+fn parses_dates_and_times_split_across_groups() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "29/02/2024 07:45 PM".parse::<Entry>()?,
+        Entry {
+            date: NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            time: NaiveTime::from_hms_opt(19, 45, 0).unwrap(),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_optional_times() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "20240301 23:59:01".parse::<Stamp>()?,
+        Stamp {
+            date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            time: NaiveTime::from_hms_opt(23, 59, 1),
+        }
+    );
+    assert_eq!(
+        "20240301".parse::<Stamp>()?,
+        Stamp {
+            date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            time: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn date_format_rejects_invalid_dates() {
+    match "29/02/2023 07:45 PM".parse::<Entry>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "input is out of range while parsing value '29/02/2023' provided by date"
+        ),
+    }
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "derive")]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::error::Error;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"(?P<name>\S+)\s(?P<shapes>.*)"#, handle_deserialize)]
+struct Drawing {
+    name: String,
+    #[serde(default)]
+    shapes: Vec<Shape>,
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"(?P<host>[^:]+):(?P<port>\d+)"#, handle_deserialize)]
+struct HostPort {
+    host: String,
+    port: u16,
+}
+
+#[test]
+fn deserializes_from_json_objects() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        serde_json::from_str::<HostPort>(r#"{"host": "localhost", "port": 8080}"#)?,
+        HostPort {
+            host: "localhost".into(),
+            port: 8080,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn deserializes_from_json_strings() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        serde_json::from_str::<Vec<HostPort>>(r#"["localhost:8080", "example.com:443"]"#)?,
+        vec![
+            HostPort {
+                host: "localhost".into(),
+                port: 8080,
+            },
+            HostPort {
+                host: "example.com".into(),
+                port: 443,
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn retains_serde_attributes_from_json() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        serde_json::from_str::<Drawing>(r#"{"name": "empty"}"#)?,
+        Drawing {
+            name: "empty".into(),
+            shapes: vec![],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn deserializes_internally_tagged_enum_fields_from_json() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        serde_json::from_str::<Drawing>(
+            r#"{
+                "name": "shapes",
+                "shapes": [
+                    {"type": "circle", "radius": 1.5},
+                    {"type": "square", "side": 2.0}
+                ]
+            }"#
+        )?,
+        Drawing {
+            name: "shapes".into(),
+            shapes: vec![Shape::Circle { radius: 1.5 }, Shape::Square { side: 2.0 }],
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<host>[^:]+):(?P<port>\d+)$"#,
+    handle_deserialize,
+    serialize
+)]
+struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+#[test]
+fn serializes_to_the_pattern_format() -> Result<(), Box<dyn Error>> {
+    let endpoint = Endpoint {
+        host: "localhost".into(),
+        port: 8080,
+    };
+    let json = serde_json::to_string(&endpoint)?;
+    assert_eq!(json, r#""localhost:8080""#);
+    assert_eq!(serde_json::from_str::<Endpoint>(&json)?, endpoint);
+
+    Ok(())
+}
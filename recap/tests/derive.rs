@@ -0,0 +1,2003 @@
+#![cfg(feature = "derive")]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::{borrow::Cow, error::Error};
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"(?P<level>\S+)(\s(?P<code>\d+))?(\s(?P<msg>.+))?"#,
+    on_missing = "default"
+)]
+struct OnMissingDefault {
+    level: String,
+    code: u16,
+    #[recap(on_missing = "none")]
+    msg: Option<String>,
+}
+
+#[test]
+fn on_missing_policy_applies_to_fields() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "info".parse::<OnMissingDefault>()?,
+        OnMissingDefault {
+            level: "info".into(),
+            code: 0,
+            msg: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+struct Origin {
+    host: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<level>\S+)(?: (?P<grade>\S))?(?: (?P<tags>\S+))?(?: (?P<origin>\S+))?$"#,
+    on_missing = "default"
+)]
+struct DefaultedEvent {
+    level: String,
+    grade: char,
+    tags: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    origin: Origin,
+}
+
+#[test]
+fn on_missing_default_resolves_empty_and_serde_defaults() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "info".parse::<DefaultedEvent>()?,
+        DefaultedEvent {
+            level: "info".into(),
+            grade: char::default(),
+            tags: Default::default(),
+            origin: Origin::default(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<account>\S+)\s(?P<balance>\S+)"#)]
+struct LedgerEntry {
+    account: String,
+    #[recap(accounting)]
+    balance: i64,
+}
+
+#[test]
+fn accounting_negates_parenthesized_values() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "cash (42)".parse::<LedgerEntry>()?,
+        LedgerEntry {
+            account: "cash".into(),
+            balance: -42,
+        }
+    );
+    assert_eq!(
+        "cash 42".parse::<LedgerEntry>()?,
+        LedgerEntry {
+            account: "cash".into(),
+            balance: 42,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct HostPort {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<method>\S+)\s(?P<addr>\S+)"#)]
+struct Connection {
+    method: String,
+    #[recap(regex = r#"(?P<host>[^:]+):(?P<port>\d+)"#)]
+    addr: HostPort,
+}
+
+#[test]
+fn field_regex_parses_nested_struct() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "GET localhost:8080".parse::<Connection>()?,
+        Connection {
+            method: "GET".into(),
+            addr: HostPort {
+                host: "localhost".into(),
+                port: 8080,
+            },
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<level>\S+)\s(?P<msg>.+)"#, bytes)]
+struct BytesEntry<'a> {
+    level: &'a str,
+    msg: String,
+}
+
+#[test]
+fn bytes_convert_with_try_from() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryFrom;
+
+    let line: &[u8] = b"info hello world";
+    assert_eq!(
+        BytesEntry::try_from(line)?,
+        BytesEntry {
+            level: "info",
+            msg: "hello world".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?s)v=(?P<value>.*)"#, bytes)]
+struct ByteValue {
+    value: Vec<u8>,
+}
+
+#[test]
+fn byte_vec_fields_hold_captured_bytes() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryFrom;
+
+    // valid UTF-8 captures are not split into a sequence of parsed numbers
+    for input in [&b"v=ab"[..], b"v=\x01\x02", b"v=1,2"] {
+        assert_eq!(ByteValue::try_from(input)?.value, &input[2..]);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<cmd>\S+):(?P<args>.*)"#)]
+struct Command {
+    cmd: String,
+    #[recap(whitespace_delimited)]
+    args: Vec<String>,
+}
+
+#[test]
+fn whitespace_delimited_skips_surrounding_whitespace() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "ls:  -l \t -a   /tmp ".parse::<Command>()?,
+        Command {
+            cmd: "ls".into(),
+            args: vec!["-l".into(), "-a".into(), "/tmp".into()],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn whitespace_delimited_blank_value_is_empty() -> Result<(), Box<dyn Error>> {
+    assert_eq!("ls:   ".parse::<Command>()?.args, Vec::<String>::new());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<cmd>\S+):(?P<args>.*)"#)]
+struct TrimmedCommand {
+    cmd: String,
+    #[recap(trim, whitespace_delimited)]
+    args: Vec<String>,
+}
+
+#[test]
+fn whitespace_delimited_combines_with_trim() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "ls: -l  /tmp ".parse::<TrimmedCommand>()?.args,
+        vec!["-l".to_string(), "/tmp".to_string()]
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<cmd>\S+):(?P<args>.*)"#)]
+struct QuotedCommand {
+    cmd: String,
+    #[recap(whitespace_delimited, quote_char = '\'')]
+    args: Vec<String>,
+}
+
+#[test]
+fn quote_char_keeps_quoted_delimiters() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "echo: 'hello world' bye".parse::<QuotedCommand>()?.args,
+        vec!["hello world".to_string(), "bye".into()]
+    );
+
+    Ok(())
+}
+
+/// A type which implements `FromStr` but not `Deserialize`
+#[derive(Debug, PartialEq)]
+struct Version(u32, u32);
+
+impl std::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s.split_once('.').ok_or("expected major.minor")?;
+        Ok(Version(
+            major.parse().map_err(|_| "invalid major")?,
+            minor.parse().map_err(|_| "invalid minor")?,
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(
+    regex = r#"(?P<name>\S+)\s(?P<version>\S+)(\s(?P<port>\S+))?"#,
+    from_str
+)]
+struct Package {
+    name: String,
+    version: Version,
+    port: Option<u16>,
+}
+
+#[test]
+fn from_str_parses_fields_without_serde() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "recap 0.1".parse::<Package>()?,
+        Package {
+            name: "recap".into(),
+            version: Version(0, 1),
+            port: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn from_str_aggregates_field_errors() {
+    match "recap 1 http".parse::<Package>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected major.minor while parsing value '1' provided by version; \
+             invalid digit found in string while parsing value 'http' provided by port"
+        ),
+    }
+}
+
+#[test]
+fn field_span_locates_capture() {
+    let input = "info 42 hello";
+    assert_eq!(OnMissingDefault::field_span(input, "code"), Some(5..7));
+    assert_eq!(OnMissingDefault::field_span("info", "code"), None);
+    assert_eq!(OnMissingDefault::field_span(input, "unknown"), None);
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<span>\S+)\s(?P<elapsed>\S+)"#)]
+struct Timing {
+    span: String,
+    #[recap(to_nanos = "micros")]
+    elapsed: u64,
+}
+
+#[test]
+fn to_nanos_scales_units() -> Result<(), Box<dyn Error>> {
+    assert_eq!("db 12.5".parse::<Timing>()?.elapsed, 12_500);
+
+    match "db -1".parse::<Timing>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "-1000 nanoseconds is out of range for the field while parsing value '-1' provided by elapsed"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?<key>\w+)=(?<value>\w+)"#)]
+struct AngleBracketNames {
+    key: String,
+    value: String,
+}
+
+#[test]
+fn angle_bracket_named_groups_validate_and_parse() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "foo=bar".parse::<AngleBracketNames>()?,
+        AngleBracketNames {
+            key: "foo".into(),
+            value: "bar".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<level>\S+)(\s(?P<msg>.+))?"#)]
+struct EmptyIfMissing<'a> {
+    level: &'a str,
+    #[recap(empty_if_missing)]
+    msg: String,
+}
+
+#[test]
+fn empty_if_missing_resolves_to_empty_string() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryFrom;
+
+    assert_eq!(
+        EmptyIfMissing::try_from("warn")?,
+        EmptyIfMissing {
+            level: "warn",
+            msg: String::new(),
+        }
+    );
+    assert_eq!(EmptyIfMissing::try_from("warn disk full")?.msg, "disk full");
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<level>[A-Z]+) \[(?P<module>\w+)\] (?P<msg>.*)$"#,
+    fallback_regex = r#"^(?P<module>\w+)\|(?P<level>[A-Z]+)\|(?P<msg>.*)$"#
+)]
+struct Record {
+    level: String,
+    module: String,
+    msg: String,
+}
+
+#[test]
+fn fallback_regex_parses_legacy_format() -> Result<(), Box<dyn Error>> {
+    let expected = Record {
+        level: "WARN".into(),
+        module: "net".into(),
+        msg: "retrying".into(),
+    };
+    assert_eq!("WARN [net] retrying".parse::<Record>()?, expected);
+    assert_eq!("net|WARN|retrying".parse::<Record>()?, expected);
+    assert!(Record::is_match("net|WARN|retrying"));
+    assert_eq!(Record::field_span("net|WARN|retrying", "level"), Some(4..8));
+    assert!("WARN: retrying".parse::<Record>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+struct Line {
+    text: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"\d+"#)]
+struct Number {
+    value: u32,
+}
+
+#[test]
+fn single_field_binds_whole_input_without_regex() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "hello\nthere".parse::<Line>()?,
+        Line {
+            text: "hello\nthere".into()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn single_field_binds_whole_match_without_named_groups() -> Result<(), Box<dyn Error>> {
+    assert_eq!("took 42ms".parse::<Number>()?, Number { value: 42 });
+
+    Ok(())
+}
+
+fn strip_ansi(s: &str) -> std::borrow::Cow<'_, str> {
+    recap::Regex::new(r"\x1b\[[0-9;]*m")
+        .expect("Failed to compile regex")
+        .replace_all(s, "")
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<level>[A-Z]+) (?P<msg>.*)$"#,
+    pre_process = "strip_ansi"
+)]
+struct TerminalLine {
+    level: String,
+    msg: String,
+}
+
+#[test]
+fn pre_process_strips_ansi_escapes() -> Result<(), Box<dyn Error>> {
+    let line = "\x1b[1;31mERROR\x1b[0m disk full";
+    assert!(TerminalLine::is_match(line));
+    assert_eq!(
+        line.parse::<TerminalLine>()?,
+        TerminalLine {
+            level: "ERROR".into(),
+            msg: "disk full".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<width>\S+) (?P<indent>\S+)"#)]
+struct Style {
+    #[recap(strip_unit = "px")]
+    width: u32,
+    #[recap(strip_unit = "em", strip_unit = "rem")]
+    indent: f64,
+}
+
+#[test]
+fn strip_unit_parses_numbers_with_units() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "100px 2.5em".parse::<Style>()?,
+        Style {
+            width: 100,
+            indent: 2.5
+        }
+    );
+    assert_eq!("100 1.5rem".parse::<Style>()?.indent, 1.5);
+
+    Ok(())
+}
+
+#[test]
+fn strip_unit_reports_unknown_units() {
+    match "100pt 2.5em".parse::<Style>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "invalid digit found in string while parsing value '100pt' provided by width"
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"level=(?P<level>info|warn)"#, case_insensitive)]
+struct Level {
+    level: String,
+}
+
+#[test]
+fn case_insensitive_matches_mixed_case_input() -> Result<(), Box<dyn Error>> {
+    assert!(Level::is_match("LEVEL=Warn"));
+    assert_eq!(
+        "LEVEL=Warn".parse::<Level>()?,
+        Level {
+            level: "Warn".into()
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"(?x) (?P<method>get|post) \s+ (?P<path>\S+)"#,
+    case_insensitive
+)]
+struct MethodLine {
+    method: String,
+    path: String,
+}
+
+#[test]
+fn case_insensitive_keeps_other_inline_flags() -> Result<(), Box<dyn Error>> {
+    for method in ["GET", "get", "Get"] {
+        assert_eq!(
+            format!("{} /index.html", method).parse::<MethodLine>()?,
+            MethodLine {
+                method: method.into(),
+                path: "/index.html".into(),
+            }
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<host>\S+) (?P<status>\d+) (?P<path>\S+)"#)]
+struct Access {
+    host: String,
+    status: u16,
+    path: Option<String>,
+}
+
+#[test]
+fn to_search_regex_matches_same_field_values() -> Result<(), Box<dyn Error>> {
+    let access: Access = "a.example.com 404 /missing".parse()?;
+    let search = access.to_search_regex(&["host", "status"]);
+    assert!(search.is_match("a.example.com 404 /other"));
+    assert!(!search.is_match("a.example.com 500 /missing"));
+    // dots in values are escaped
+    assert!(!search.is_match("aXexample.com 404 /missing"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"cpu=(?P<cpu>\S+) mem=(?P<mem>\S+)"#)]
+struct Utilization {
+    #[recap(percent)]
+    cpu: f64,
+    #[recap(percent = "required")]
+    mem: f32,
+}
+
+#[test]
+fn percent_parses_fractions() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "cpu=0.5 mem=25%".parse::<Utilization>()?,
+        Utilization {
+            cpu: 0.5,
+            mem: 0.25
+        }
+    );
+    assert!("cpu=95% mem=0.25".parse::<Utilization>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Change {
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?:(?P<kind>added|removed) )?(?P<path>\S+)"#)]
+struct FileChange {
+    #[recap(variant_by = "kind")]
+    change: Change,
+    path: String,
+}
+
+#[test]
+fn variant_by_selects_enum_variant() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "removed src/lib.rs".parse::<FileChange>()?,
+        FileChange {
+            change: Change::Removed,
+            path: "src/lib.rs".into(),
+        }
+    );
+    match "src/lib.rs".parse::<FileChange>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "missing capture kind selecting the variant of field change"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(\w+)=(\d+)"#, field_order = "name, count")]
+struct Assignment {
+    count: u32,
+    name: String,
+}
+
+#[test]
+fn field_order_binds_numbered_groups() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "apples=3".parse::<Assignment>()?,
+        Assignment {
+            name: "apples".into(),
+            count: 3
+        }
+    );
+    assert_eq!(Assignment::field_span("apples=3", "count"), Some(7..8));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<key>\w+)=(?P<value>.*)$"#, trim_newline)]
+struct Setting<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+#[test]
+fn trim_newline_strips_single_line_terminator() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryInto;
+
+    for input in ["a=b c\n", "a=b c\r\n", "a=b c"] {
+        let setting: Setting = input.try_into()?;
+        assert_eq!(
+            setting,
+            Setting {
+                key: "a",
+                value: "b c"
+            }
+        );
+        assert!(Setting::is_match(input));
+    }
+    // only a single terminator is stripped
+    assert!(!Setting::is_match("a=b\n\n"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<width>\d+)x(?P<height>\d+)"#)]
+struct Dimensions {
+    width: u32,
+    height: u32,
+    #[serde(skip_deserializing)]
+    area: u32,
+    #[recap(skip)]
+    label: Option<String>,
+}
+
+#[test]
+fn skipped_fields_resolve_to_defaults() -> Result<(), Box<dyn Error>> {
+    let mut dimensions: Dimensions = "3x4".parse()?;
+    assert_eq!(
+        dimensions,
+        Dimensions {
+            width: 3,
+            height: 4,
+            area: 0,
+            label: None,
+        }
+    );
+    dimensions.area = dimensions.width * dimensions.height;
+    assert_eq!(dimensions.area, 12);
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<name>\S+)"#)]
+struct Annotated {
+    name: String,
+    #[recap(skip)]
+    #[serde(default)]
+    origin: Origin,
+    #[recap(skip)]
+    notes: std::collections::HashMap<String, String>,
+    #[recap(skip)]
+    grade: char,
+}
+
+#[test]
+fn skipped_fields_resolve_to_defaults_of_any_type() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "widget".parse::<Annotated>()?,
+        Annotated {
+            name: "widget".into(),
+            origin: Origin::default(),
+            notes: Default::default(),
+            grade: char::default(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<station>\w+) (?P<reading>\S+)"#)]
+struct Measurement {
+    station: String,
+    #[recap(missing_as_none = "NaN", missing_as_none = "-")]
+    reading: Option<f64>,
+}
+
+#[test]
+fn missing_as_none_resolves_sentinels_to_none() -> Result<(), Box<dyn Error>> {
+    assert_eq!("a NaN".parse::<Measurement>()?.reading, None);
+    assert_eq!("a -".parse::<Measurement>()?.reading, None);
+    assert_eq!("a 1.5".parse::<Measurement>()?.reading, Some(1.5));
+    assert!("a nan".parse::<Measurement>()?.reading.unwrap().is_nan());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"lines (?P<lines>\S+)"#)]
+struct Selection {
+    lines: std::ops::Range<u32>,
+}
+
+#[test]
+fn range_fields_parse_delimited_bounds() -> Result<(), Box<dyn Error>> {
+    assert_eq!("lines 10-20".parse::<Selection>()?.lines, 10..20);
+    assert!("lines 20-10".parse::<Selection>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<pid>\d+) (?P<user>\w+) (?P<command>.+)$"#,
+    collapse_whitespace
+)]
+struct Process {
+    pid: u32,
+    user: String,
+    command: String,
+}
+
+#[test]
+fn collapse_whitespace_normalizes_spacing() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "42 \t root\t\tsleep    10".parse::<Process>()?,
+        Process {
+            pid: 42,
+            user: "root".into(),
+            command: "sleep 10".into(),
+        }
+    );
+    assert!(Process::is_match("42\t\troot sleep"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<command>\w+)(?P<verbose_marker> -v)?$"#)]
+struct Invocation {
+    command: String,
+    #[recap(flag_group = "verbose_marker")]
+    verbose: bool,
+}
+
+#[test]
+fn flag_group_sets_bool_from_group_participation() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "build -v".parse::<Invocation>()?,
+        Invocation {
+            command: "build".into(),
+            verbose: true,
+        }
+    );
+    assert_eq!(
+        "build".parse::<Invocation>()?,
+        Invocation {
+            command: "build".into(),
+            verbose: false,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Coordinate {
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn recap_macro_parses_inline() -> Result<(), Box<dyn Error>> {
+    let input = String::from(" (1, -2) ");
+    assert_eq!(
+        recap::recap!(r#"\((?P<x>-?\d+), (?P<y>-?\d+)\)"#, input.trim() => Coordinate)?,
+        Coordinate { x: 1, y: -2 }
+    );
+    assert!(recap::recap!(r#"(?P<x>\d+),(?P<y>\d+)"#, "1,z" => Coordinate).is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<count>\d+) (?P<items>.*)$"#)]
+struct Counted {
+    count: usize,
+    #[recap(whitespace_delimited, counted_by = "count")]
+    items: Vec<String>,
+}
+
+#[test]
+fn counted_by_checks_sequence_length() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "3 a b c".parse::<Counted>()?,
+        Counted {
+            count: 3,
+            items: vec!["a".into(), "b".into(), "c".into()],
+        }
+    );
+    match "2 a b c".parse::<Counted>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected 2 elements of field items, as counted by count, but found 3"
+        ),
+    }
+
+    Ok(())
+}
+
+fn property_pattern() -> String {
+    ["name", "value"]
+        .iter()
+        .map(|name| format!(r#"(?P<{}>\w+)"#, name))
+        .collect::<Vec<_>>()
+        .join("=")
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex_fn = "property_pattern")]
+struct Property {
+    name: String,
+    value: String,
+}
+
+#[test]
+fn regex_fn_builds_pattern_at_runtime() -> Result<(), Box<dyn Error>> {
+    assert!(Property::is_match("color=red"));
+    assert_eq!(
+        "color=red".parse::<Property>()?,
+        Property {
+            name: "color".into(),
+            value: "red".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<tag>\w+):(?P<value>\S+)"#)]
+struct Tagged<T> {
+    value: T,
+    tag: String,
+}
+
+#[test]
+fn derives_for_generic_types() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "count:42".parse::<Tagged<u32>>()?,
+        Tagged {
+            value: 42,
+            tag: "count".into(),
+        }
+    );
+    assert_eq!(
+        Tagged::<f64>::try_from("ratio:0.5")?,
+        Tagged {
+            value: 0.5,
+            tag: "ratio".into(),
+        }
+    );
+    assert!("count:x".parse::<Tagged<u32>>().is_err());
+    assert!(Tagged::<u32>::is_match("count:x"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<ts>\d+) (?P<message>.+)$"#)]
+struct Event {
+    #[recap(keep_raw = "ts_raw")]
+    ts: i64,
+    ts_raw: String,
+    message: String,
+}
+
+#[test]
+fn keep_raw_populates_companion_field() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "0042 started".parse::<Event>()?,
+        Event {
+            ts: 42,
+            ts_raw: "0042".into(),
+            message: "started".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?:(?P<ts>\d+) )?(?P<message>.+)$"#)]
+struct OptionalEvent {
+    #[recap(keep_raw = "ts_raw")]
+    ts: Option<i64>,
+    ts_raw: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?:(?P<ts>\d+) )?(?P<message>.+)$"#)]
+struct DefaultedRawEvent {
+    #[recap(keep_raw = "ts_raw", on_missing = "default")]
+    ts: Option<i64>,
+    ts_raw: String,
+    message: String,
+}
+
+#[test]
+fn keep_raw_companion_resolves_when_capture_is_absent() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "started".parse::<OptionalEvent>()?,
+        OptionalEvent {
+            ts: None,
+            ts_raw: None,
+            message: "started".into(),
+        }
+    );
+    assert_eq!(
+        "0042 started".parse::<OptionalEvent>()?.ts_raw.as_deref(),
+        Some("0042")
+    );
+    assert_eq!(
+        "started".parse::<DefaultedRawEvent>()?,
+        DefaultedRawEvent {
+            ts: None,
+            ts_raw: String::new(),
+            message: "started".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<coord>\S+) (?P<color>\S+)$"#)]
+struct Marker {
+    coord: (f64, f64),
+    color: (u8, u8, u8),
+}
+
+#[test]
+fn tuples_split_into_elements() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "12.5,-48.2 255,128,0".parse::<Marker>()?,
+        Marker {
+            coord: (12.5, -48.2),
+            color: (255, 128, 0),
+        }
+    );
+    match "12.5 255,128,0".parse::<Marker>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected 2 elements but found 1 in value '12.5' provided by coord"
+        ),
+    }
+    match "12.5,48.2 255,128,0,1".parse::<Marker>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected 3 elements but found 4 in value '255,128,0,1' provided by color"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"[a-z]+(?:-[a-z]+)*"#)]
+struct Slug(String);
+
+#[test]
+fn newtypes_validate_their_value() -> Result<(), Box<dyn Error>> {
+    assert_eq!("hello-world".parse::<Slug>()?, Slug("hello-world".into()));
+    assert!(Slug::is_match("hello-world"));
+    assert!(!Slug::is_match("Hello-world"));
+    assert!("hello world".parse::<Slug>().is_err());
+    assert!("hello-".parse::<Slug>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"\d+"#)]
+struct Count(u8);
+
+#[test]
+fn newtype_errors_name_the_newtype() {
+    match "300".parse::<Count>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "number too large to fit in target type while parsing value '300' provided by Count"
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[repr(u16)]
+enum Status {
+    Ok = 200,
+    NotFound = 404,
+    Unavailable = 503,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<path>\S+) (?P<status>\d+)$"#)]
+struct Response {
+    path: String,
+    #[recap(enum_by_discriminant)]
+    status: Status,
+}
+
+#[test]
+fn enums_resolve_by_discriminant() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "/index.html 404".parse::<Response>()?,
+        Response {
+            path: "/index.html".into(),
+            status: Status::NotFound,
+        }
+    );
+    match "/index.html 418".parse::<Response>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "discriminant 418 provided by status is out of range, expected one of 200, 404, 503"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[repr(u128)]
+enum Checksum {
+    Empty = 0,
+    Full = u128::MAX,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[repr(i8)]
+enum Offset {
+    Behind = -1,
+    Ahead = 1,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<checksum>\d+) (?P<offset>-?\d+)$"#)]
+struct Block {
+    #[recap(enum_by_discriminant = "u128")]
+    checksum: Checksum,
+    #[recap(enum_by_discriminant)]
+    offset: Offset,
+}
+
+#[test]
+fn enums_resolve_by_discriminant_in_their_repr() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        format!("{} -1", u128::MAX).parse::<Block>()?,
+        Block {
+            checksum: Checksum::Full,
+            offset: Offset::Behind,
+        }
+    );
+    // the bits of `u128::MAX` read as an i128 are those of -1
+    match "0 340282366920938463463374607431768211455".parse::<Block>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "discriminant 340282366920938463463374607431768211455 provided by offset is out of range, expected one of -1, 1"
+        ),
+    }
+    match "1 1".parse::<Block>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "discriminant 1 provided by checksum is out of range, expected one of 0, 340282366920938463463374607431768211455"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<levels>\d*) #(?P<color>[0-9a-fA-F]*)$"#)]
+struct Packed {
+    #[recap(chunk_width = 2)]
+    levels: Vec<u8>,
+    #[recap(chunk_width = 2, radix = 16)]
+    color: Vec<u8>,
+}
+
+#[test]
+fn sequences_split_into_fixed_width_chunks() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "010203 #ff8000".parse::<Packed>()?,
+        Packed {
+            levels: vec![1, 2, 3],
+            color: vec![255, 128, 0],
+        }
+    );
+    match "01020 #ff8000".parse::<Packed>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected a length which is a multiple of 2 in value '01020' provided by levels"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<requestId>\S+) (?P<statusCode>\d+)$"#,
+    rename_all = "camelCase"
+)]
+struct CamelRequest {
+    request_id: String,
+    status_code: u16,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<RequestId>\S+) (?P<StatusCode>\d+)$"#,
+    rename_all = "PascalCase"
+)]
+struct PascalRequest {
+    request_id: String,
+    status_code: u16,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<REQUEST_ID>\S+) (?P<STATUS_CODE>\d+)$"#,
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+struct ScreamingRequest {
+    request_id: String,
+    status_code: u16,
+}
+
+#[test]
+fn capture_names_follow_rename_all_conventions() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "abc 200".parse::<CamelRequest>()?,
+        CamelRequest {
+            request_id: "abc".into(),
+            status_code: 200,
+        }
+    );
+    assert_eq!(
+        "abc 200".parse::<PascalRequest>()?,
+        PascalRequest {
+            request_id: "abc".into(),
+            status_code: 200,
+        }
+    );
+    assert_eq!(
+        "abc 200".parse::<ScreamingRequest>()?,
+        ScreamingRequest {
+            request_id: "abc".into(),
+            status_code: 200,
+        }
+    );
+
+    Ok(())
+}
+
+// the derive validates these patterns regardless of case and underscores, while
+// the runtime resolves each group exactly with `RenameRule::apply`
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<aBC>\d+)$"#, rename_all = "camelCase")]
+struct CamelRuns {
+    a__b_c: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<ABC>\d+)$"#, rename_all = "PascalCase")]
+struct PascalRuns {
+    a__b_c: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<A__B_C>\d+)$"#, rename_all = "SCREAMING_SNAKE_CASE")]
+struct ScreamingRuns {
+    a__b_c: u32,
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<requestId>\S+)(?: (?P<statusCode>\d+))?$"#,
+    rename_all = "camelCase",
+    from_str
+)]
+struct CamelParsed {
+    request_id: String,
+    status_code: Option<u16>,
+}
+
+#[test]
+fn rename_all_applies_to_from_str_fields() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "abc 200".parse::<CamelParsed>()?,
+        CamelParsed {
+            request_id: "abc".into(),
+            status_code: Some(200),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rename_all_agrees_with_validation_on_runs_of_underscores() -> Result<(), Box<dyn Error>> {
+    assert_eq!("1".parse::<CamelRuns>()?, CamelRuns { a__b_c: 1 });
+    assert_eq!("2".parse::<PascalRuns>()?, PascalRuns { a__b_c: 2 });
+    assert_eq!("3".parse::<ScreamingRuns>()?, ScreamingRuns { a__b_c: 3 });
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+struct FixedWidthDate {
+    #[recap(width = 4)]
+    year: u16,
+    #[recap(width = 2)]
+    month: u8,
+    #[recap(width = 2)]
+    day: u8,
+}
+
+#[test]
+fn regex_builds_from_field_widths() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "20240115".parse::<FixedWidthDate>()?,
+        FixedWidthDate {
+            year: 2024,
+            month: 1,
+            day: 15,
+        }
+    );
+    assert!("2024011".parse::<FixedWidthDate>().is_err());
+    assert!("202401150".parse::<FixedWidthDate>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<level>[A-Z]+) (?P<code>\d+)$"#)]
+struct StatusLine {
+    level: String,
+    code: u16,
+}
+
+#[test]
+fn from_reader_parses_matching_lines() -> Result<(), Box<dyn Error>> {
+    let input = std::io::Cursor::new(b"INFO 200\n# comment\nWARN 404\r\nERROR 99999\n".to_vec());
+    let lines: Vec<_> = StatusLine::from_reader(input).collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0].as_ref().map_err(ToString::to_string),
+        Ok(&StatusLine {
+            level: "INFO".into(),
+            code: 200,
+        })
+    );
+    assert_eq!(
+        lines[1].as_ref().map_err(ToString::to_string),
+        Ok(&StatusLine {
+            level: "WARN".into(),
+            code: 404,
+        })
+    );
+    let err = lines[2].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<cmd>\w+)(?: (?P<arg>\w+))?(?: (?P<flag>--\w+))?$"#)]
+struct ShellCommand {
+    cmd: String,
+    arg: Option<String>,
+    flag: Option<String>,
+    #[recap(group_count)]
+    groups: usize,
+}
+
+#[test]
+fn group_count_reflects_participating_groups() -> Result<(), Box<dyn Error>> {
+    assert_eq!("ls".parse::<ShellCommand>()?.groups, 1);
+    assert_eq!("ls src".parse::<ShellCommand>()?.groups, 2);
+    assert_eq!("ls --all".parse::<ShellCommand>()?.groups, 2);
+    assert_eq!(
+        "ls src --all".parse::<ShellCommand>()?,
+        ShellCommand {
+            cmd: "ls".into(),
+            arg: Some("src".into()),
+            flag: Some("--all".into()),
+            groups: 3,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<job>\S+) (?P<outcome>\S+)$"#)]
+struct JobResult {
+    job: String,
+    #[recap(ok_prefix = "OK:", err_prefix = "ERR:")]
+    outcome: Result<u32, String>,
+}
+
+#[test]
+fn result_fields_resolve_by_prefix() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "build OK:42".parse::<JobResult>()?,
+        JobResult {
+            job: "build".into(),
+            outcome: Ok(42),
+        }
+    );
+    assert_eq!(
+        "deploy ERR:timeout".parse::<JobResult>()?,
+        JobResult {
+            job: "deploy".into(),
+            outcome: Err("timeout".into()),
+        }
+    );
+    match "deploy MAYBE:1".parse::<JobResult>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected value 'MAYBE:1' provided by outcome to start with 'OK:' or 'ERR:'"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+struct PaddedRecord<'a> {
+    #[recap(width = 8, trim_end)]
+    name: &'a str,
+    #[recap(width = 6, trim_start)]
+    amount: u32,
+}
+
+#[test]
+fn one_sided_trims_keep_the_other_side() -> Result<(), Box<dyn Error>> {
+    let record = PaddedRecord::try_from("  ada       42")?;
+    assert_eq!(
+        record,
+        PaddedRecord {
+            name: "  ada",
+            amount: 42,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<time>\d{2}:\d{2})(?P<offset>Z|[+-]\S+)$"#)]
+struct LocalTime {
+    time: String,
+    #[recap(utc_offset)]
+    offset: i32,
+}
+
+#[test]
+fn utc_offsets_resolve_to_signed_seconds() -> Result<(), Box<dyn Error>> {
+    assert_eq!("12:00+05:30".parse::<LocalTime>()?.offset, 19_800);
+    assert_eq!("12:00-05:30".parse::<LocalTime>()?.offset, -19_800);
+    assert_eq!("12:00-0800".parse::<LocalTime>()?.offset, -28_800);
+    assert_eq!("12:00+00:00".parse::<LocalTime>()?.offset, 0);
+    assert_eq!("12:00-00:00".parse::<LocalTime>()?.offset, 0);
+    assert_eq!("12:00Z".parse::<LocalTime>()?.offset, 0);
+    assert!("12:00+5:30".parse::<LocalTime>().is_err());
+    assert!("12:00+05:75".parse::<LocalTime>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<host>\S+) (?P<message>.+)$"#)]
+struct Remark<'a> {
+    host: &'a str,
+    #[serde(borrow)]
+    #[recap(dedup_whitespace_in_field)]
+    message: Cow<'a, str>,
+}
+
+#[test]
+fn field_whitespace_collapses_within_a_single_field() -> Result<(), Box<dyn Error>> {
+    let remark = Remark::try_from("web-1 disk  almost\t full")?;
+    assert_eq!(remark.host, "web-1");
+    assert_eq!(remark.message, "disk almost full");
+    assert!(matches!(remark.message, Cow::Owned(_)));
+
+    let remark = Remark::try_from("web-1 disk almost full")?;
+    assert!(matches!(remark.message, Cow::Borrowed("disk almost full")));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?s)^(?P<title>[^\n]+)\n(?P<items>.*)$"#)]
+struct Checklist {
+    title: String,
+    #[recap(lines)]
+    items: Vec<String>,
+}
+
+#[test]
+fn lines_split_block_captures() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "groceries\n  eggs\r\nmilk  \n\n   \nbread\n".parse::<Checklist>()?,
+        Checklist {
+            title: "groceries".into(),
+            items: vec!["eggs".into(), "milk".into(), "bread".into()],
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<key>\w+)\s*=\s*(?P<value>\w+)$"#,
+    charset = "[a-z0-9_ =]"
+)]
+struct ConfigLine {
+    key: String,
+    value: String,
+}
+
+#[test]
+fn charset_rejects_stray_characters() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "retries = 3".parse::<ConfigLine>()?,
+        ConfigLine {
+            key: "retries".into(),
+            value: "3".into(),
+        }
+    );
+    assert!(ConfigLine::is_match("retries = 3"));
+    assert!(!ConfigLine::is_match("retries =\t3"));
+    match "retries =\t3".parse::<ConfigLine>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "unexpected character \"\\t\" at byte 9 of input 'retries =\t3'"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<id>\d+) (?P<name>\S+)$"#)]
+struct TypedId<T> {
+    id: u64,
+    name: String,
+    marker: std::marker::PhantomData<T>,
+}
+
+#[test]
+fn phantom_fields_need_no_capture() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "7 seven".parse::<TypedId<u32>>()?,
+        TypedId {
+            id: 7,
+            name: "seven".into(),
+            marker: std::marker::PhantomData,
+        }
+    );
+    assert_eq!(
+        "7 seven".parse::<TaggedId<u32>>()?.marker,
+        std::marker::PhantomData
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<id>\d+) (?P<name>\S+)$"#)]
+struct TaggedId<T> {
+    id: u64,
+    name: String,
+    #[recap(on_missing = "default")]
+    marker: std::marker::PhantomData<T>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<name>\S+)(?: (?P<alias>\S+))?$"#)]
+struct CompactName {
+    name: Box<str>,
+    alias: Option<Box<str>>,
+}
+
+#[test]
+fn deserializes_boxed_str_fields() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "ada lovelace".parse::<CompactName>()?,
+        CompactName {
+            name: "ada".into(),
+            alias: Some("lovelace".into()),
+        }
+    );
+    assert_eq!(
+        "ada".parse::<CompactName>()?,
+        CompactName {
+            name: "ada".into(),
+            alias: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<level>\w+): (?P<code>\d+)$"#, ascii)]
+struct AsciiStatus {
+    level: String,
+    code: u32,
+}
+
+#[test]
+fn ascii_patterns_match_ascii_classes_only() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "warn: 42".parse::<AsciiStatus>()?,
+        AsciiStatus {
+            level: "warn".into(),
+            code: 42,
+        }
+    );
+    assert!(!AsciiStatus::is_match("wärn: 42"));
+    assert!(!AsciiStatus::is_match("warn: ٤٢"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^\[(?P<level>[A-Z]+)\] (?P<time>\d{2}:\d{2})"#)]
+struct LogHeader {
+    time: String,
+    level: String,
+    #[recap(rest)]
+    message: String,
+}
+
+#[test]
+fn rest_fields_keep_the_tail() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "[WARN] 09:15 disk usage at 91% (threshold 90%)".parse::<LogHeader>()?,
+        LogHeader {
+            time: "09:15".into(),
+            level: "WARN".into(),
+            message: " disk usage at 91% (threshold 90%)".into(),
+        }
+    );
+    assert_eq!("[INFO] 09:15".parse::<LogHeader>()?.message, "");
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<endpoint>\S+) (?P<errors>\S+)$"#)]
+struct ErrorBudget {
+    endpoint: String,
+    #[recap(delimiter_regex = "/")]
+    errors: (u64, u64),
+}
+
+#[test]
+fn tuple_fields_split_on_their_delimiter() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "/health 200/500".parse::<ErrorBudget>()?,
+        ErrorBudget {
+            endpoint: "/health".into(),
+            errors: (200, 500),
+        }
+    );
+    match "/health 200/500/7".parse::<ErrorBudget>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected 2 elements but found 3 in value '200/500/7' provided by errors"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"^\d{1,5}$"#, from_str)]
+struct Port {
+    value: u16,
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"\d{1,5}"#, from_str)]
+struct PortNumber(u16);
+
+#[test]
+fn single_fields_parse_the_whole_match_from_str() -> Result<(), Box<dyn Error>> {
+    assert_eq!("8080".parse::<Port>()?, Port { value: 8080 });
+    assert_eq!("8080".parse::<PortNumber>()?, PortNumber(8080));
+    for input in ["99999", "port 8080", "123456"] {
+        assert!(input.parse::<Port>().is_err());
+        assert!(input.parse::<PortNumber>().is_err());
+    }
+    match "99999".parse::<Port>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "number too large to fit in target type while parsing value '99999' provided by value"
+        ),
+    }
+    match "99999".parse::<PortNumber>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "number too large to fit in target type while parsing value '99999' provided by PortNumber"
+        ),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<host>[a-z.]+):(?P<port>\d+)$"#)]
+struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+#[test]
+fn matched_collapses_failures_to_none() {
+    assert_eq!(
+        Endpoint::matched("example.com:443"),
+        Some(Endpoint {
+            host: "example.com".into(),
+            port: 443,
+        })
+    );
+    // matches the regex, but the port does not fit a u16
+    assert!(Endpoint::is_match("example.com:99999"));
+    assert_eq!(Endpoint::matched("example.com:99999"), None);
+    assert_eq!(Endpoint::matched("example.com"), None);
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"\[(?P<k>\w+):(?P<v>\d+)\]"#, handle_deserialize)]
+struct Item {
+    k: String,
+    v: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<batch>\w+) (?P<items>.*)$"#)]
+struct Batch {
+    batch: String,
+    #[recap(regex = r#"\[(?P<k>\w+):(?P<v>\d+)\]"#)]
+    items: Vec<Item>,
+}
+
+#[test]
+fn sequence_fields_repeat_their_regex() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "b1 [a:1][b:2][c:3]".parse::<Batch>()?,
+        Batch {
+            batch: "b1".into(),
+            items: vec![
+                Item {
+                    k: "a".into(),
+                    v: 1,
+                },
+                Item {
+                    k: "b".into(),
+                    v: 2,
+                },
+                Item {
+                    k: "c".into(),
+                    v: 3,
+                },
+            ],
+        }
+    );
+    assert_eq!("b2 ".parse::<Batch>()?.items, vec![]);
+
+    Ok(())
+}
+
+recap::module_default_regex! {
+    r#"^(?P<key>\w+)=(?P<value>\S+)$"#;
+
+    #[derive(Debug, Deserialize, PartialEq, Recap)]
+    struct EnvVar {
+        key: String,
+        value: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Recap)]
+    struct NumericVar {
+        key: String,
+        value: i64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Recap)]
+    #[recap(regex = r#"^(?P<key>[\w-]+): (?P<value>.+)$"#)]
+    struct HeaderVar {
+        key: String,
+        value: String,
+    }
+}
+
+#[test]
+fn module_default_regex_is_shared() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "HOME=/root".parse::<EnvVar>()?,
+        EnvVar {
+            key: "HOME".into(),
+            value: "/root".into(),
+        }
+    );
+    assert_eq!(
+        "DEPTH=-3".parse::<NumericVar>()?,
+        NumericVar {
+            key: "DEPTH".into(),
+            value: -3,
+        }
+    );
+    // a type's own regex takes precedence over the default
+    assert_eq!(
+        "content-type: text/plain".parse::<HeaderVar>()?,
+        HeaderVar {
+            key: "content-type".into(),
+            value: "text/plain".into(),
+        }
+    );
+    assert!(!HeaderVar::is_match("HOME=/root"));
+
+    Ok(())
+}
+
+#[test]
+fn exposes_the_compiled_regex() {
+    assert!(std::ptr::eq(EnvVar::regex(), EnvVar::regex()));
+    assert_eq!(
+        EnvVar::regex()
+            .capture_names()
+            .flatten()
+            .collect::<Vec<_>>(),
+        vec!["key", "value"]
+    );
+    let keys: Vec<_> = Item::regex()
+        .captures_iter("[a:1] [b:2]")
+        .map(|caps| caps["k"].to_string())
+        .collect();
+    assert_eq!(keys, vec!["a", "b"]);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Verb {
+    Get,
+    Post,
+    Delete,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<verb>\S+) (?P<path>\S+)(?: (?P<status>\S+))?"#)]
+struct AccessEntry {
+    #[recap(
+        map = "GET => Get",
+        map = "POST => Post",
+        map = "DEL => Delete",
+        map_strict
+    )]
+    verb: Verb,
+    path: String,
+    #[recap(map = "OK => 200", map = "NOT_FOUND => 404")]
+    status: Option<String>,
+}
+
+#[test]
+fn map_remaps_captured_values() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "DEL /items/1 NOT_FOUND".parse::<AccessEntry>()?,
+        AccessEntry {
+            verb: Verb::Delete,
+            path: "/items/1".into(),
+            status: Some("404".into()),
+        }
+    );
+    // unmapped values pass through unless the map is strict
+    assert_eq!(
+        "GET / 500".parse::<AccessEntry>()?.status,
+        Some("500".into())
+    );
+    match "PUT /items/1".parse::<AccessEntry>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(err.to_string(), "unmapped value 'PUT' provided by verb"),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<verb>\S+) (?P<status>\S+)"#)]
+struct BorrowedAccess<'a> {
+    #[recap(map = "DEL => DELETE")]
+    verb: &'a str,
+    #[recap(map = "OK => 200", map = "NOT_FOUND => 404")]
+    status: u16,
+}
+
+#[test]
+fn map_applies_to_borrowed_and_numeric_fields() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryFrom;
+
+    assert_eq!(
+        BorrowedAccess::try_from("DEL NOT_FOUND")?,
+        BorrowedAccess {
+            verb: "DELETE",
+            status: 404,
+        }
+    );
+    assert_eq!(
+        BorrowedAccess::try_from("GET 500")?,
+        BorrowedAccess {
+            verb: "GET",
+            status: 500,
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<a>\d+)-(?P<b>\d+)"#, anchored)]
+struct Span {
+    a: u32,
+    b: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(
+    regex = r#"(?x)
+        (?P<a>\d+) # start
+        -
+        (?P<b>\d+) # end"#,
+    anchored
+)]
+struct VerboseSpan {
+    a: u32,
+    b: u32,
+}
+
+#[test]
+fn anchored_matches_whole_inputs_only() -> Result<(), Box<dyn Error>> {
+    assert_eq!("12-34".parse::<Span>()?, Span { a: 12, b: 34 });
+    assert!("xx12-34yy".parse::<Span>().is_err());
+    assert!(!Span::is_match("xx12-34yy"));
+    // leading inline flags still apply to the whole pattern
+    assert_eq!(
+        "12-34".parse::<VerboseSpan>()?,
+        VerboseSpan { a: 12, b: 34 }
+    );
+    assert!("12-34yy".parse::<VerboseSpan>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<ids>\S.*\S) (?P<point>\(.*\))"#)]
+struct Bracketed {
+    #[recap(strip_brackets, delimiter_regex = r#",\s*"#)]
+    ids: Vec<u32>,
+    #[recap(strip_brackets = "()")]
+    point: (i32, i32),
+}
+
+#[test]
+fn strip_brackets_splits_bracketed_lists() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "[1, 2, 3] (-4,5)".parse::<Bracketed>()?,
+        Bracketed {
+            ids: vec![1, 2, 3],
+            point: (-4, 5),
+        }
+    );
+    assert_eq!("[] (0,0)".parse::<Bracketed>()?.ids, Vec::<u32>::new());
+    match "[1, 2]] (0,0)".parse::<Bracketed>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected value '[1, 2]]' provided by ids to be enclosed in balanced '[' and ']'"
+        ),
+    }
+    assert!("1, 2 (0,0)".parse::<Bracketed>().is_err());
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(format = "{from} to {to} = {dist:\\d+}")]
+struct Route {
+    from: String,
+    to: String,
+    dist: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(format = "v{major:\\d+}.{minor:\\d+} ({{{channel:[a-z]+}}})")]
+struct Release {
+    major: u32,
+    minor: u32,
+    channel: String,
+}
+
+#[test]
+fn format_translates_placeholders_into_groups() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "London to Dublin = 464".parse::<Route>()?,
+        Route {
+            from: "London".into(),
+            to: "Dublin".into(),
+            dist: 464,
+        }
+    );
+    assert!("London to Dublin = far".parse::<Route>().is_err());
+    // literal text is matched literally, so the `.` only matches a `.`
+    assert_eq!(
+        "v1.12 ({beta})".parse::<Release>()?,
+        Release {
+            major: 1,
+            minor: 12,
+            channel: "beta".into(),
+        }
+    );
+    assert!(!Release::is_match("v1x12 ({beta})"));
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(\d+)x(\d+)(?: @(?P<rate>\d+)Hz)?"#)]
+struct Resolution(u32, u32, Option<u16>);
+
+#[test]
+fn tuple_structs_capture_groups_in_order() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "1920x1080 @60Hz".parse::<Resolution>()?,
+        Resolution(1920, 1080, Some(60))
+    );
+    assert_eq!(
+        "1280x720".parse::<Resolution>()?,
+        Resolution(1280, 720, None)
+    );
+    assert_eq!(Resolution::field_span("1280x720", "_1"), Some(5..8));
+    assert!("1280x".parse::<Resolution>().is_err());
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+#![cfg(all(feature = "derive", feature = "encoding"))]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::error::Error;
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<name>\S+) (?P<city>\S+)"#, encoding = "latin1")]
+struct Latin1Record {
+    name: String,
+    city: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<name>\S+) (?P<city>\S+)"#, encoding = "utf-16le")]
+struct Utf16Record {
+    name: String,
+    city: String,
+}
+
+#[test]
+fn decodes_latin1_before_parsing() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        Latin1Record::try_from_encoded(b"Ren\xe9 Z\xfcrich")?,
+        Latin1Record {
+            name: "René".into(),
+            city: "Zürich".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decodes_utf16_before_parsing() -> Result<(), Box<dyn Error>> {
+    let bytes: Vec<u8> = "René Zürich"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    assert_eq!(
+        Utf16Record::try_from_encoded(&bytes)?,
+        Utf16Record {
+            name: "René".into(),
+            city: "Zürich".into(),
+        }
+    );
+    match Utf16Record::try_from_encoded(&bytes[1..]) {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(err.to_string(), "input is not valid UTF-16LE"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "derive")]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::error::Error;
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"(?P<host>[^:]+):(?P<port>\d+)"#, handle_deserialize)]
+struct HostPort {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<method>\S+)\s(?P<addr>\S+)"#)]
+struct Connection {
+    method: String,
+    addr: HostPort,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"(?P<method>\S+)\s(?P<addr>\S+)"#)]
+struct LegacyConnection {
+    method: String,
+    #[recap(regex = r#"(?P<port>\d+)@(?P<host>\S+)"#)]
+    addr: HostPort,
+}
+
+#[test]
+fn nested_type_parses_with_its_own_regex() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "GET localhost:8080".parse::<Connection>()?,
+        Connection {
+            method: "GET".into(),
+            addr: HostPort {
+                host: "localhost".into(),
+                port: 8080,
+            },
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn field_regex_overrides_nested_type_regex() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "GET 8080@localhost".parse::<LegacyConnection>()?,
+        LegacyConnection {
+            method: "GET".into(),
+            addr: HostPort {
+                host: "localhost".into(),
+                port: 8080,
+            },
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<name>[^>]+)(?:>(?P<next>.+))?$"#, handle_deserialize)]
+struct Path {
+    name: String,
+    next: Option<Box<Self>>,
+}
+
+#[test]
+fn recursive_types_parse_each_level() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "a>b>c".parse::<Path>()?,
+        Path {
+            name: "a".into(),
+            next: Some(Box::new(Path {
+                name: "b".into(),
+                next: Some(Box::new(Path {
+                    name: "c".into(),
+                    next: None,
+                })),
+            })),
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(
+    regex = r#"^(?P<host>[^:\s]+)(?::(?P<port>\d+))?(?:\s+(?P<scheme>\w+))?$"#,
+    handle_deserialize
+)]
+#[serde(default)]
+struct Endpoint {
+    host: String,
+    port: u16,
+    scheme: String,
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Endpoint {
+            host: "localhost".into(),
+            port: 443,
+            scheme: "https".into(),
+        }
+    }
+}
+
+#[test]
+fn container_default_fills_missing_captures() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "example.com".parse::<Endpoint>()?,
+        Endpoint {
+            host: "example.com".into(),
+            port: 443,
+            scheme: "https".into(),
+        }
+    );
+    assert_eq!(
+        "example.com:8080 http".parse::<Endpoint>()?,
+        Endpoint {
+            host: "example.com".into(),
+            port: 8080,
+            scheme: "http".into(),
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#![cfg(all(feature = "derive", feature = "iso8601"))]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::{error::Error, time::Duration};
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^took (?P<elapsed>\S+)$"#)]
+struct Timing {
+    #[recap(iso8601_duration)]
+    elapsed: Duration,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?:timeout (?P<timeout>\S+))?$"#)]
+struct Timeout {
+    #[recap(iso8601_duration)]
+    timeout: Option<Duration>,
+}
+
+fn elapsed(input: &str) -> Result<Duration, recap::Error> {
+    input.parse::<Timing>().map(|timing| timing.elapsed)
+}
+
+#[test]
+fn parses_iso8601_durations() -> Result<(), Box<dyn Error>> {
+    assert_eq!(elapsed("took PT1H30M15S")?, Duration::from_secs(5415));
+    assert_eq!(elapsed("took PT90M")?, Duration::from_secs(5400));
+    assert_eq!(elapsed("took PT0.25S")?, Duration::from_millis(250));
+    assert_eq!(elapsed("took PT1,5S")?, Duration::from_millis(1500));
+    assert_eq!(elapsed("took P1DT12H")?, Duration::from_secs(129_600));
+    assert_eq!(elapsed("took P2W")?, Duration::from_secs(1_209_600));
+    assert_eq!(elapsed("took P3D")?, Duration::from_secs(259_200));
+    assert_eq!(
+        "timeout PT5S".parse::<Timeout>()?,
+        Timeout {
+            timeout: Some(Duration::from_secs(5))
+        }
+    );
+    assert_eq!("".parse::<Timeout>()?, Timeout { timeout: None });
+
+    Ok(())
+}
+
+#[test]
+fn rejects_unsupported_iso8601_durations() {
+    for (input, error) in [
+        (
+            "took P1Y",
+            "years are not supported in ISO-8601 durations while parsing value 'P1Y' provided by elapsed",
+        ),
+        (
+            "took P1M",
+            "months are not supported in ISO-8601 durations while parsing value 'P1M' provided by elapsed",
+        ),
+        (
+            "took PT1.5H",
+            "fractional hours are not supported in ISO-8601 durations while parsing value 'PT1.5H' provided by elapsed",
+        ),
+        (
+            "took PT",
+            "invalid ISO-8601 duration while parsing value 'PT' provided by elapsed",
+        ),
+        (
+            "took PT1S1M",
+            "invalid ISO-8601 duration while parsing value 'PT1S1M' provided by elapsed",
+        ),
+        (
+            "took -PT1S",
+            "invalid ISO-8601 duration while parsing value '-PT1S' provided by elapsed",
+        ),
+        (
+            "took 1H",
+            "invalid ISO-8601 duration while parsing value '1H' provided by elapsed",
+        ),
+    ] {
+        match elapsed(input) {
+            Ok(_) => panic!("{} should have failed", input),
+            Err(err) => assert_eq!(err.to_string(), error),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+#![cfg(all(feature = "derive", feature = "json"))]
+
+use recap::Recap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::error::Error;
+
+#[derive(Debug, Deserialize, Serialize, Recap)]
+#[recap(regex = r#"(?P<level>\S+)\s(?P<code>\d+)"#)]
+struct Entry {
+    level: String,
+    code: u16,
+}
+
+#[test]
+fn converts_parsed_values_to_json() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "warn 42".parse::<Entry>()?.to_json_value()?,
+        json!({ "level": "warn", "code": 42 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn captures_deserialize_into_json_maps() -> Result<(), Box<dyn Error>> {
+    let re = recap::Regex::new(r#"(?P<level>\S+)\s(?P<code>\d+)(?:\s(?P<detail>.+))?"#)?;
+    let map: serde_json::Map<String, serde_json::Value> = recap::from_captures(&re, "warn 42")?;
+    assert_eq!(
+        serde_json::Value::Object(map),
+        json!({ "level": "warn", "code": "42" })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+#![cfg(all(feature = "derive", feature = "smallvec"))]
+
+use recap::Recap;
+use serde::Deserialize;
+use smallvec::{smallvec, SmallVec};
+use std::error::Error;
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"^(?P<name>\w+): (?P<ports>\S+)$"#)]
+struct Listener {
+    name: String,
+    ports: SmallVec<[u16; 4]>,
+}
+
+#[test]
+fn deserializes_small_vec_sequences() -> Result<(), Box<dyn Error>> {
+    let listener: Listener = "web: 80,443".parse()?;
+    assert_eq!(listener.ports, SmallVec::<[u16; 4]>::from_slice(&[80, 443]));
+    assert!(!listener.ports.spilled());
+
+    let spilled: Listener = "web: 1,2,3,4,5".parse()?;
+    let expected: SmallVec<[u16; 4]> = smallvec![1, 2, 3, 4, 5];
+    assert_eq!(spilled.ports, expected);
+    assert!(spilled.ports.spilled());
+
+    assert!("web: 80,http".parse::<Listener>().is_err());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "futures")]
+
+use futures::{executor::block_on, stream, StreamExt};
+use recap::{stream::parse_stream, Regex};
+use serde::Deserialize;
+use std::error::Error;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Request {
+    method: String,
+    status: u16,
+}
+
+#[test]
+fn parses_each_line_of_a_stream() -> Result<(), Box<dyn Error>> {
+    let re = Regex::new(r#"^(?P<method>[A-Z]+) (?P<status>\d{3})$"#)?;
+    let lines = stream::iter(
+        ["GET 200", "oops", "POST 500"]
+            .iter()
+            .map(|line| line.to_string()),
+    );
+    let results: Vec<_> = block_on(parse_stream::<Request, _>(&re, lines).collect());
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[0].as_ref().ok(),
+        Some(&Request {
+            method: "GET".into(),
+            status: 200,
+        })
+    );
+    assert!(results[1].is_err());
+    assert_eq!(
+        results[2].as_ref().ok(),
+        Some(&Request {
+            method: "POST".into(),
+            status: 500,
+        })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?i)(?P<method>get|post)"#, case_insensitive)]
+struct Request {
+    method: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Origin {
+    host: String,
+}
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"^(?P<level>\S+)(?: (?P<origin>\S+))?$"#)]
+struct Event {
+    level: String,
+    #[recap(on_missing = "default")]
+    origin: Origin,
+}
+
+fn main() {}
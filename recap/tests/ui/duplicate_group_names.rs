@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<a>\d+)-(?P<b>\d+)|(?P<a>\w+)"#)]
+struct Duplicated {
+    a: String,
+    b: String,
+}
+
+fn main() {}
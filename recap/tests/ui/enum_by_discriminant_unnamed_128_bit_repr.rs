@@ -0,0 +1,18 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[repr(u128)]
+enum Checksum {
+    Empty = 0,
+    Full = u128::MAX,
+}
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"^(?P<checksum>\d+)$"#)]
+struct Block {
+    #[recap(enum_by_discriminant)]
+    checksum: Checksum,
+}
+
+fn main() {}
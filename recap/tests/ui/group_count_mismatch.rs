@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<a>\d+)"#)]
+struct Mismatched {
+    a: u32,
+    b: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<ratio>\S+)"#)]
+struct Ratio {
+    #[recap(percent = "sometimes")]
+    ratio: f64,
+}
+
+fn main() {}
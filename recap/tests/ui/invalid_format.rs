@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(format = "{from} to {to")]
+struct Route {
+    from: String,
+    to: String,
+}
+
+fn main() {}
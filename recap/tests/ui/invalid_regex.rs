@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<a>\d+"#)]
+struct Unclosed {
+    a: u32,
+}
+
+fn main() {}
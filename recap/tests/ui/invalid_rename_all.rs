@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<someValue>\d+)"#, rename_all = "Train-Case")]
+struct Renamed {
+    some_value: u32,
+}
+
+fn main() {}
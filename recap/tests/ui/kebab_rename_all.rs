@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<some_value>\d+)"#, rename_all = "kebab-case")]
+struct Value {
+    some_value: u32,
+}
+
+fn main() {}
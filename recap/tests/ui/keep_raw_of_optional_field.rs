@@ -0,0 +1,13 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"^(?:(?P<ts>\d+) )?(?P<message>.+)$"#)]
+struct Event {
+    #[recap(keep_raw = "ts_raw")]
+    ts: Option<i64>,
+    ts_raw: String,
+    message: String,
+}
+
+fn main() {}
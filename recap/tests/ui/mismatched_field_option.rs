@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<offset>\S+)"#)]
+struct Offset {
+    #[recap(accounting)]
+    offset: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+struct Unmatched {
+    a: u32,
+    b: u32,
+}
+
+fn main() {}
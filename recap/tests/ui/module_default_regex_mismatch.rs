@@ -0,0 +1,15 @@
+use recap::Recap;
+use serde::Deserialize;
+
+recap::module_default_regex! {
+    r#"^(?P<key>\w+)=(?P<value>\S+)$"#;
+
+    #[derive(Debug, Deserialize, Recap)]
+    struct Flag {
+        name: String,
+        enabled: bool,
+        scope: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<a>\d+)"#)]
+enum Choice {
+    A,
+    B,
+}
+
+fn main() {}
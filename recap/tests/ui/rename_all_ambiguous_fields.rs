@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<aB>\d+) (?P<ab>\d+)"#, rename_all = "camelCase")]
+struct Value {
+    a_b: u32,
+    ab: u32,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<someValue>\d+) (?P<label>\S+)"#, rename_all = "camelCase")]
+struct Value {
+    some_value: u32,
+    #[serde(rename = "name")]
+    label: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Origin {
+    host: String,
+}
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<name>\S+)"#)]
+struct Entry {
+    name: String,
+    #[recap(skip)]
+    origin: Origin,
+}
+
+fn main() {}
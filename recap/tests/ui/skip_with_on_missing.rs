@@ -0,0 +1,13 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<name>\S+)"#)]
+struct Entry {
+    name: String,
+    #[recap(skip, on_missing = "error")]
+    #[serde(skip)]
+    seen: bool,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(\d+)-(\d+)-(\d+)"#)]
+struct Pair(u32, u32);
+
+fn main() {}
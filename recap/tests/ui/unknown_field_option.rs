@@ -0,0 +1,11 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<word>.+)"#)]
+struct Word {
+    #[recap(trimm)]
+    word: String,
+}
+
+fn main() {}
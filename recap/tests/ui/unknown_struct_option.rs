@@ -0,0 +1,10 @@
+use recap::Recap;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Recap)]
+#[recap(regex = r#"(?P<word>\S+)"#, anchord)]
+struct Word {
+    word: String,
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "derive", feature = "uuid"))]
+
+use recap::Recap;
+use serde::Deserialize;
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"request (?P<id>\S+)"#)]
+struct Request {
+    id: Uuid,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Recap)]
+#[recap(regex = r#"request (?P<id>\S+)"#)]
+struct SimpleRequest {
+    #[recap(uuid_format = "simple")]
+    id: Uuid,
+}
+
+const ID: Uuid = Uuid::from_u128(0x550e8400_e29b_41d4_a716_446655440000);
+
+#[test]
+fn parses_hyphenated_uuids() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "request 550e8400-e29b-41d4-a716-446655440000".parse::<Request>()?,
+        Request { id: ID }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_simple_uuids() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "request 550e8400e29b41d4a716446655440000".parse::<Request>()?,
+        Request { id: ID }
+    );
+    assert_eq!(
+        "request 550e8400e29b41d4a716446655440000".parse::<SimpleRequest>()?,
+        SimpleRequest { id: ID }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn uuid_format_rejects_other_formats() {
+    match "request 550e8400-e29b-41d4-a716-446655440000".parse::<SimpleRequest>() {
+        Ok(_) => panic!("should have failed"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "expected a simple uuid while parsing value '550e8400-e29b-41d4-a716-446655440000' provided by id"
+        ),
+    }
+}
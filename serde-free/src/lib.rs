@@ -0,0 +1,2 @@
+//! This crate intentionally depends on recap alone. Its tests verify
+//! that derived code never requires a direct dependency on serde
@@ -0,0 +1,32 @@
+use recap::Recap;
+use std::error::Error;
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"(?P<host>[^:]+):(?P<port>\d+)"#, handle_deserialize)]
+struct HostPort {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Recap)]
+#[recap(regex = r#"(?P<method>\S+)\s(?P<addr>\S+)"#, handle_deserialize)]
+struct Connection {
+    method: String,
+    addr: HostPort,
+}
+
+#[test]
+fn handle_deserialize_requires_only_recap() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        "GET localhost:8080".parse::<Connection>()?,
+        Connection {
+            method: "GET".into(),
+            addr: HostPort {
+                host: "localhost".into(),
+                port: 8080,
+            },
+        }
+    );
+
+    Ok(())
+}